@@ -1,49 +1,294 @@
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::fs;
 
 fn main() {
+    // cargo run -- src/input.txt [--part1 | --part2] [--table extra.txt]
     let args: Vec<String> = env::args().collect();
     let path = args
         .get(1)
         .expect("path is required: cargo run -- src/input.txt");
     let readme = fs::read_to_string(path).expect("could not read file");
-    println!("{}", get_text_value(readme.as_str()));
+
+    let mut modes = vec![];
+    if !args.iter().any(|a| a == "--part2") {
+        modes.push(Mode::Digits);
+    }
+    if !args.iter().any(|a| a == "--part1") {
+        modes.push(Mode::Words);
+    }
+
+    for mode in modes {
+        let mut vocabulary = Vocabulary::for_mode(mode);
+        if let Some(table) = args
+            .iter()
+            .position(|a| a == "--table")
+            .and_then(|i| args.get(i + 1))
+        {
+            // extra words only make sense when words are matched at all
+            if mode == Mode::Words {
+                let table = fs::read_to_string(table).expect("could not read table");
+                if let Err(e) = vocabulary.add_table(&table) {
+                    println!("{}", e);
+                    return;
+                }
+            }
+        }
+
+        let matcher = Matcher::new(&vocabulary);
+        match get_text_value(readme.as_str(), &matcher) {
+            Ok(sum) => println!("{:?}: {}", mode, sum),
+            Err(lines) => {
+                let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+                println!("{:?}: no digits on lines {}", mode, lines.join(", "));
+            }
+        }
+    }
 }
 
 const WORDS2DIGITS: &[&str] = &[
     "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
 
-fn get_line_value(s: &str) -> u32 {
-    let bytes = s.as_bytes();
-    // all digits in a string are collected into a vec
-    // TODO may just look at the beginning and the end ignoring everything in between
-    let mut digits: Vec<u32> = Vec::new();
+// part 1 only counts digits, part 2 also counts spelled out ones
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Digits,
+    Words,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Vocabulary {
+    tokens: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    fn digits() -> Vocabulary {
+        Vocabulary {
+            tokens: (0..10).map(|d| (d.to_string(), d)).collect(),
+        }
+    }
+
+    fn english() -> Vocabulary {
+        let mut vocabulary = Vocabulary::digits();
+        for (i, word) in WORDS2DIGITS.iter().enumerate() {
+            vocabulary.add(word, i as u32 + 1);
+        }
+        vocabulary
+    }
 
-    for (i, c) in bytes.iter().enumerate() {
-        if c.is_ascii_digit() {
-            digits.push((*c as char).to_digit(10).unwrap());
+    fn for_mode(mode: Mode) -> Vocabulary {
+        match mode {
+            Mode::Digits => Vocabulary::digits(),
+            Mode::Words => Vocabulary::english(),
         }
+    }
 
-        let word_position = WORDS2DIGITS.iter().position(|word| {
-            if i + word.len() > bytes.len() {
-                return false;
+    fn add(&mut self, token: &str, digit: u32) {
+        self.tokens.push((token.to_string(), digit));
+    }
+
+    // one "token digit" pair per line, e.g. "eins 1", blank lines and # comments are skipped
+    fn add_table(&mut self, s: &str) -> Result<(), String> {
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
             }
 
-            return word.as_bytes() == &bytes[i..(i+word.len())];
-        });
-        if let Some(word_index) = word_position {
-            digits.push(word_index as u32 + 1);
-            // TODO skip the following WORDS2DIGITS[word_index].len() symbols
+            let (token, digit) = line
+                .split_once(char::is_whitespace)
+                .ok_or(format!("line {}: expected a token and a digit", i + 1))?;
+            let digit = digit
+                .trim()
+                .parse()
+                .ok()
+                .filter(|d| *d < 10)
+                .ok_or(format!("line {}: {} is not a digit", i + 1, digit.trim()))?;
+
+            self.add(token, digit);
         }
+
+        Ok(())
     }
+}
 
-    return digits.first().unwrap_or(&0) * 10 + 
-        digits.last().unwrap_or(&0);
+// Aho-Corasick automaton over the vocabulary: a single pass over the line reports every
+// token, overlapping ones too, so "eightwo" gives both 8 and 2
+#[derive(Debug)]
+struct Automaton {
+    // transitions for every byte, failure links already folded in
+    next: Vec<[usize; 256]>,
+    // (length, digit) of every token ending in a state, longest first
+    output: Vec<Vec<(usize, u32)>>,
+    longest: usize,
 }
 
-fn get_text_value(s: &str) -> u32 {
-    s.lines().map(|line| get_line_value(line)).sum()
+impl Automaton {
+    fn new(tokens: impl Iterator<Item = (Vec<u8>, u32)>) -> Automaton {
+        let mut children: Vec<HashMap<u8, usize>> = vec![HashMap::new()];
+        let mut output: Vec<Vec<(usize, u32)>> = vec![vec![]];
+        let mut longest = 0;
+
+        for (token, digit) in tokens {
+            let mut state = 0;
+            for b in token.iter() {
+                state = match children[state].get(b) {
+                    Some(next) => *next,
+                    None => {
+                        let id = children.len();
+                        children.push(HashMap::new());
+                        output.push(vec![]);
+                        children[state].insert(*b, id);
+                        id
+                    }
+                };
+            }
+            if !output[state].iter().any(|(len, _)| *len == token.len()) {
+                output[state].push((token.len(), digit));
+            }
+            longest = longest.max(token.len());
+        }
+
+        // breadth first, so the failure state is always complete before it's needed
+        let mut next = vec![[0; 256]; children.len()];
+        let mut fail = vec![0; children.len()];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+
+        for (b, child) in children[0].iter() {
+            next[0][*b as usize] = *child;
+            queue.push_back(*child);
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let inherited = output[fail[state]].clone();
+            output[state].extend(inherited);
+
+            let fallback = next[fail[state]];
+            for (b, target) in next[state].iter_mut().enumerate() {
+                match children[state].get(&(b as u8)) {
+                    Some(child) => {
+                        fail[*child] = fallback[b];
+                        *target = *child;
+                        queue.push_back(*child);
+                    }
+                    None => *target = fallback[b],
+                }
+            }
+        }
+
+        Automaton {
+            next,
+            output,
+            longest,
+        }
+    }
+}
+
+// Looks for the first token from the start of the line and for the last one from its end,
+// the backward automaton is built from the reversed tokens and reads the line reversed
+#[derive(Debug)]
+struct Matcher {
+    forward: Automaton,
+    backward: Automaton,
+}
+
+impl Matcher {
+    fn new(vocabulary: &Vocabulary) -> Matcher {
+        let tokens = || {
+            vocabulary
+                .tokens
+                .iter()
+                .map(|(token, digit)| (token.bytes().collect::<Vec<u8>>(), *digit))
+        };
+
+        Matcher {
+            forward: Automaton::new(tokens()),
+            backward: Automaton::new(tokens().map(|(mut token, digit)| {
+                token.reverse();
+                (token, digit)
+            })),
+        }
+    }
+
+    // every (start, digit) in the order the tokens end
+    #[cfg(test)]
+    fn matches<'a>(&'a self, s: &'a str) -> impl Iterator<Item = (usize, u32)> + 'a {
+        let automaton = &self.forward;
+        s.bytes()
+            .enumerate()
+            .scan(0, move |state, (i, b)| {
+                *state = automaton.next[*state][b as usize];
+                Some((i, *state))
+            })
+            .flat_map(move |(i, state)| {
+                automaton.output[state]
+                    .iter()
+                    .map(move |(len, digit)| (i + 1 - len, *digit))
+            })
+    }
+
+    // The first token to end isn't always the first to start, a longer one around it
+    // could start earlier, so keep reading until no token could start before the best one
+    fn first(&self, s: &str) -> Option<u32> {
+        let automaton = &self.forward;
+        let mut state = 0;
+        let mut best: Option<(usize, u32)> = None;
+
+        for (i, b) in s.bytes().enumerate() {
+            if best.is_some_and(|(start, _)| i + 1 >= start + automaton.longest) {
+                break;
+            }
+
+            state = automaton.next[state][b as usize];
+            if let Some((len, digit)) = automaton.output[state].first() {
+                let start = i + 1 - len;
+                if best.is_none_or(|(best_start, _)| start < best_start) {
+                    best = Some((start, *digit));
+                }
+            }
+        }
+
+        best.map(|(_, digit)| digit)
+    }
+
+    // read backwards, the first token to end is the one starting last in the line
+    fn last(&self, s: &str) -> Option<u32> {
+        let automaton = &self.backward;
+        let mut state = 0;
+
+        for b in s.bytes().rev() {
+            state = automaton.next[state][b as usize];
+            if let Some((_, digit)) = automaton.output[state].first() {
+                return Some(*digit);
+            }
+        }
+
+        None
+    }
+}
+
+fn get_line_value(s: &str, matcher: &Matcher) -> Option<u32> {
+    Some(matcher.first(s)? * 10 + matcher.last(s)?)
+}
+
+// the sum, or the numbers of the lines without any digit
+fn get_text_value(s: &str, matcher: &Matcher) -> Result<u32, Vec<usize>> {
+    let mut sum = 0;
+    let mut missing = vec![];
+
+    for (i, line) in s.lines().enumerate() {
+        match get_line_value(line, matcher) {
+            Some(value) => sum += value,
+            None => missing.push(i + 1),
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(sum)
+    } else {
+        Err(missing)
+    }
 }
 
 #[cfg(test)]
@@ -52,33 +297,197 @@ mod tests {
 
     #[test]
     fn test_get_line_value() {
-        assert_eq!(12, get_line_value("t1est2"));
-        assert_eq!(55, get_line_value("5"));
-        assert_eq!(99, get_line_value("asdfas9"));
-        assert_eq!(0, get_line_value("asdfadsf"));
-        assert_eq!(77, get_line_value("treb7uchet"));
+        let matcher = Matcher::new(&Vocabulary::english());
+        let get_line_value = |s| get_line_value(s, &matcher);
 
-        assert_eq!(11, get_line_value("one"));
-        assert_eq!(12, get_line_value("onetwo"));
+        assert_eq!(Some(12), get_line_value("t1est2"));
+        assert_eq!(Some(55), get_line_value("5"));
+        assert_eq!(Some(99), get_line_value("asdfas9"));
+        assert_eq!(None, get_line_value("asdfadsf"));
+        assert_eq!(Some(77), get_line_value("treb7uchet"));
 
-        assert_eq!(24, get_line_value("xtwone3four"));
-        assert_eq!(29, get_line_value("two1nine"));
-        assert_eq!(83, get_line_value("8wo3"));
-        assert_eq!(83, get_line_value("eightwo3"));
-        assert_eq!(83, get_line_value("eightwothree"));
-        assert_eq!(42, get_line_value("4nineeightseven2"));
+        assert_eq!(Some(11), get_line_value("one"));
+        assert_eq!(Some(12), get_line_value("onetwo"));
+
+        assert_eq!(Some(24), get_line_value("xtwone3four"));
+        assert_eq!(Some(29), get_line_value("two1nine"));
+        assert_eq!(Some(83), get_line_value("8wo3"));
+        assert_eq!(Some(83), get_line_value("eightwo3"));
+        assert_eq!(Some(83), get_line_value("eightwothree"));
+        assert_eq!(Some(42), get_line_value("4nineeightseven2"));
     }
 
     #[test]
     fn test_get_text_value() {
-        assert_eq!(
-            142,
-            get_text_value(
-                "1abc2
+        let text = "1abc2
         pqr3stu8vwx
         a1b2c3d4e5f
-        treb7uchet"
-            )
+        treb7uchet";
+
+        assert_eq!(
+            Ok(142),
+            get_text_value(text, &Matcher::new(&Vocabulary::digits()))
+        );
+        assert_eq!(
+            Ok(142),
+            get_text_value(text, &Matcher::new(&Vocabulary::english()))
+        );
+        assert_eq!(
+            Err(vec![2, 4]),
+            get_text_value("1\nabc\n2\n\n3", &Matcher::new(&Vocabulary::digits()))
+        );
+    }
+
+    #[test]
+    fn test_modes() {
+        let text = "two1nine
+        eightwothree
+        abcone2threexyz
+        xtwone3four
+        4nineeightseven2
+        zoneight234
+        7pqrstsixteen";
+
+        let digits = Matcher::new(&Vocabulary::for_mode(Mode::Digits));
+        let words = Matcher::new(&Vocabulary::for_mode(Mode::Words));
+
+        // "eightwothree" has no digits at all
+        assert_eq!(Err(vec![2]), get_text_value(text, &digits));
+        assert_eq!(Ok(281), get_text_value(text, &words));
+    }
+
+    #[test]
+    fn test_matches() {
+        let matcher = Matcher::new(&Vocabulary::english());
+        let matches: Vec<(usize, u32)> = matcher.matches("eightwone8").collect();
+        assert_eq!(vec![(0, 8), (4, 2), (6, 1), (9, 8)], matches);
+
+        assert_eq!(
+            0,
+            Matcher::new(&Vocabulary::digits())
+                .matches("eightwo")
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let mut vocabulary = Vocabulary::english();
+        vocabulary
+            .add_table("# german\neins 1\nzwei 2\n\ndrei 3\nzwo 2")
+            .unwrap();
+        let matcher = Matcher::new(&vocabulary);
+
+        assert_eq!(Some(12), get_line_value("xeinsxzwo", &matcher));
+        assert_eq!(Some(31), get_line_value("dreizweieins", &matcher));
+        // "neun" isn't in the table
+        assert_eq!(Some(28), get_line_value("zweightneun", &matcher));
+
+        // one token inside another: the outer one starts first, the inner one last
+        let mut vocabulary = Vocabulary::digits();
+        vocabulary.add_table("seventeen 7\nteen 1").unwrap();
+        let matcher = Matcher::new(&vocabulary);
+        assert_eq!(Some(71), get_line_value("seventeen", &matcher));
+
+        assert_eq!(
+            Err("line 2: x is not a digit".to_string()),
+            Vocabulary::digits().add_table("eins 1\nzwei x")
+        );
+        assert_eq!(
+            Err("line 1: 12 is not a digit".to_string()),
+            Vocabulary::digits().add_table("zwoelf 12")
+        );
+        assert_eq!(
+            Err("line 1: expected a token and a digit".to_string()),
+            Vocabulary::digits().add_table("eins")
         );
     }
+
+    #[test]
+    fn test_first_last() {
+        let matcher = Matcher::new(&Vocabulary::english());
+        assert_eq!(Some(8), matcher.first("eightwone8"));
+        assert_eq!(Some(8), matcher.last("eightwone8"));
+        assert_eq!(Some(1), matcher.last("eightwone"));
+        assert_eq!(Some(2), matcher.last("xxeightwoxx"));
+        assert_eq!(None, matcher.first("eigh"));
+        assert_eq!(None, matcher.last(""));
+
+        // "teen" ends before "seventeen", but starts later
+        let mut vocabulary = Vocabulary::digits();
+        vocabulary.add_table("seventeen 7\nteen 1\nten 5").unwrap();
+        let matcher = Matcher::new(&vocabulary);
+        assert_eq!(Some(7), matcher.first("xseventeenx"));
+        assert_eq!(Some(1), matcher.last("xseventeenx"));
+        assert_eq!(Some(5), matcher.first("seventen"));
+    }
+
+    fn generate(seed: &mut u64, lines: usize, width: usize) -> String {
+        let tokens = [
+            "one", "eight", "two", "nine", "3", "7", "seven", "eigh", "on",
+        ];
+        let mut text = String::new();
+
+        for _ in 0..lines {
+            let mut line = String::new();
+            while line.len() < width {
+                *seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let r = (*seed >> 33) as usize;
+                if r.is_multiple_of(50) {
+                    line.push_str(tokens[r / 50 % tokens.len()]);
+                } else {
+                    line.push((b'a' + (r % 26) as u8) as char);
+                }
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+
+        text
+    }
+
+    // what the search from both ends has to agree with
+    fn full_scan(s: &str, matcher: &Matcher) -> Option<u32> {
+        let first = matcher.matches(s).min_by_key(|(start, _)| *start)?;
+        let last = matcher.matches(s).max_by_key(|(start, _)| *start)?;
+        Some(first.1 * 10 + last.1)
+    }
+
+    #[test]
+    fn test_same_as_full_scan() {
+        let matcher = Matcher::new(&Vocabulary::english());
+        let mut seed = 1;
+        let text = generate(&mut seed, 500, 60);
+
+        for line in text.lines() {
+            assert_eq!(
+                full_scan(line, &matcher),
+                get_line_value(line, &matcher),
+                "{}",
+                line
+            );
+        }
+    }
+
+    // cargo test --release bench_get_text_value -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_get_text_value() {
+        // about 4MB, lines of a thousand letters with a token every fifty bytes or so
+        let matcher = Matcher::new(&Vocabulary::english());
+        let mut seed = 1;
+        let text = generate(&mut seed, 4000, 1000);
+
+        let started = std::time::Instant::now();
+        let scanned: u32 = text.lines().filter_map(|l| full_scan(l, &matcher)).sum();
+        println!("full scan: {:?}", started.elapsed());
+
+        let started = std::time::Instant::now();
+        let searched = get_text_value(&text, &matcher);
+        println!("from both ends: {:?}", started.elapsed());
+
+        assert_eq!(Ok(scanned), searched);
+    }
 }
@@ -1,12 +1,25 @@
-use std::{fs, str::FromStr, fmt::{self, write, Display}};
+use std::{env, fs, str::FromStr, fmt::{self, Display}};
 
 
 fn main() {
     let input = fs::read_to_string("./src/input.txt").expect("could not read file");
     let game: Game = input.parse().unwrap();
-    let route = game.get_route();
-    println!("Route: {:?}", route);
-    println!("Farthest point: {:?}", game.route_farthest_point(route));
+    let route = match game.get_route() {
+        Ok(route) => route,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    // cargo run -- --render [--color]
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--render") {
+        print!("{}", game.render(&route, args.iter().any(|a| a == "--color")));
+    }
+
+    println!("Farthest point: {:?}", game.route_farthest_point(&route));
+    println!("Enclosed tiles: {:?}", game.enclosed_tiles(&route));
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -21,6 +34,29 @@ enum Tile {
     SE,
 }
 
+// every tile of the loop together with its (row, col) position
+type Route = Vec<((i32, i32), Tile)>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum RouteError {
+    NoLoop,
+    AmbiguousStart(Vec<Tile>),
+    OutOfBounds((i32, i32)),
+}
+
+impl Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self {
+            RouteError::NoLoop => write!(f, "no loop goes through the start tile"),
+            RouteError::AmbiguousStart(tiles) => {
+                let tiles: Vec<String> = tiles.iter().map(|t| t.to_string()).collect();
+                write!(f, "start tile is ambiguous, could be any of {}", tiles.join(" "))
+            }
+            RouteError::OutOfBounds(pos) => write!(f, "pipe at {:?} leads outside of the map", pos),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Game {
     tiles: Vec<Vec<Tile>>,
@@ -33,7 +69,7 @@ impl FromStr for Game {
     fn from_str(input: &str) -> Result<Game, Self::Err> {
         let mut game = Game{start:(0, 0), tiles: Vec::new()};
 
-        for (row, line) in input.trim().lines().enumerate().into_iter() {
+        for (row, line) in input.trim().lines().enumerate() {
             let tiles: Vec<Tile> = line.trim().split("").
                 filter(|t| !t.is_empty()).
                 map(|t| t.parse().unwrap()).collect();
@@ -83,130 +119,202 @@ impl Display for Tile {
 }
 
 impl Game {
-    fn route_farthest_point(&self, route: Vec<Tile>) -> i32 {
-        if route.len() % 2 == 0 {
-            return route.len() as i32 / 2
+    // a closed loop on a grid always has an even length
+    fn route_farthest_point(&self, route: &Route) -> i32 {
+        route.len() as i32 / 2
+    }
+
+    fn enclosed_tiles(&self, route: &Route) -> usize {
+        self.enclosed_map(route).iter().flatten().filter(|inside| **inside).count()
+    }
+
+    fn route_map(&self, route: &Route) -> Vec<Vec<bool>> {
+        let mut on_route = vec![vec![false; self.tiles[0].len()]; self.tiles.len()];
+        for (pos, _) in route.iter() {
+            on_route[pos.0 as usize][pos.1 as usize] = true;
         }
 
-        return route.len() as i32 / 2 + 1;
+        on_route
     }
-    fn get_route(&self) -> Vec<Tile> {
-        for x in -1..=1 {
-            for y in -1..=1 {
-                if x == y {
+
+    // scanline parity: going left to right, every loop tile with a north connection
+    // flips between outside and inside. Pairs like L-7 flip once and F-7 flip twice,
+    // so tiles squeezed between pipes are handled as well.
+    fn enclosed_map(&self, route: &Route) -> Vec<Vec<bool>> {
+        let on_route = self.route_map(route);
+        let start_tile = self.start_tile(route);
+        let mut result = vec![vec![false; self.tiles[0].len()]; self.tiles.len()];
+
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            let mut inside = false;
+
+            for (col, tile) in tiles.iter().enumerate() {
+                if !on_route[row][col] {
+                    result[row][col] = inside;
                     continue;
                 }
 
-                if !self.can_enter(self.start, (self.start.0+x, self.start.1+y)) {
-                    // println!("cant move from start tile {:?} to {:?}", self.start, (self.start.0+x, self.start.1+y));
-                    continue;
+                let tile = if matches!(tile, Tile::Start) { start_tile } else { *tile };
+                if tile.connects_north() {
+                    inside = !inside;
                 }
+            }
+        }
 
-                // println!("moving from start tile {:?} to {:?}", self.start, (self.start.0+x, self.start.1+y));
-                let candidate_route = self.walk(self.start, (self.start.0+x, self.start.1+y));
+        result
+    }
 
-                if candidate_route.len() > 0 && matches!(candidate_route.last().unwrap(), Tile::Start) {
-                    return candidate_route 
+    // draws the loop with box-drawing characters, enclosed tiles as I
+    // and everything else not on the loop dimmed (or as a dot without colour)
+    fn render(&self, route: &Route, color: bool) -> String {
+        let on_route = self.route_map(route);
+        let enclosed = self.enclosed_map(route);
+        let start_tile = self.start_tile(route);
+        let mut result = String::new();
+
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                let cell = if on_route[row][col] {
+                    match (tile, color) {
+                        (Tile::Start, true) => format!("\x1b[1;31m{}\x1b[0m", start_tile.box_char()),
+                        (Tile::Start, false) => start_tile.box_char().to_string(),
+                        (_, true) => format!("\x1b[1;33m{}\x1b[0m", tile.box_char()),
+                        (_, false) => tile.box_char().to_string(),
+                    }
+                } else if enclosed[row][col] {
+                    if color { "\x1b[1;32mI\x1b[0m".to_string() } else { "I".to_string() }
+                } else if color {
+                    format!("\x1b[2m{}\x1b[0m", tile)
                 } else {
-                    println!("---- done with candidate route starting from {:?}----", (self.start.0+x, self.start.1+y));
-                }
+                    "·".to_string()
+                };
+
+                result.push_str(&cell);
             }
+
+            result.push('\n');
+        }
+
+        result
+    }
+
+    // the pipe hidden under S, inferred from the two route tiles connected to it
+    fn start_tile(&self, route: &Route) -> Tile {
+        let first = route.first().map(|(pos, _)| *pos);
+        let last = route.len().checked_sub(2).and_then(|i| route.get(i)).map(|(pos, _)| *pos);
+
+        match (first, last) {
+            (Some(first), Some(last)) => Tile::from_offsets(
+                (first.0 - self.start.0, first.1 - self.start.1),
+                (last.0 - self.start.0, last.1 - self.start.1),
+            ),
+            _ => Tile::Ground,
         }
-        
-        panic!("no route found")
     }
 
-    fn walk(&self, mut prev_pos: (i32, i32), mut pos: (i32, i32)) -> Vec<Tile> {
-        let mut result: Vec<Tile> = Vec::new();
+    fn get_route(&self) -> Result<Route, RouteError> {
+        let mut routes: Vec<Route> = Vec::new();
+        let mut error = RouteError::NoLoop;
 
-        loop {
-            let next_tile_coords = self.get_next_tile_coords(prev_pos, pos);
-
-            match next_tile_coords {
-                Some(coords) => {   
-                    let next_tile = self.tiles[coords.0 as usize][coords.1 as usize];
-                    result.push(next_tile);
-
-                    if matches!(next_tile, Tile::Start) {
-                        println!(
-                            "made a loop {:?} {:?} to {:?} {:?}",
-                            pos,
-                            self.tiles[pos.0 as usize][pos.1 as usize],
-                            coords,
-                            next_tile
-                        );     
-
-                        break
-                    }
+        for (row, col) in [(-1, 0), (0, -1), (0, 1), (1, 0)] {
+            let next = (self.start.0 + row, self.start.1 + col);
 
-                    // println!(
-                    //     "moving from {:?} {:?} to {:?} {:?}",
-                    //     pos,
-                    //     self.tiles[pos.0 as usize][pos.1 as usize],
-                    //     coords,
-                    //     next_tile
-                    // );     
-
-                    prev_pos = pos;
-                    pos = coords;
-                },
-                None => {
-                    println!(
-                        "cant move from {:?} {}",
-                        pos,
-                        self.tiles[pos.0 as usize][pos.1 as usize],
-                    );     
-
-                    // self.print_around(pos.0, pos.1);
-
-                    break
-                },
+            if !self.can_enter(self.start, next) {
+                continue;
+            }
+
+            match self.walk(self.start, next) {
+                Ok(route) => routes.push(route),
+                Err(e @ RouteError::OutOfBounds(_)) if error == RouteError::NoLoop => error = e,
+                Err(_) => {},
             }
         }
 
-        result
+        // every loop is found twice, once in each direction
+        let mut start_tiles: Vec<Tile> = Vec::new();
+        for route in routes.iter() {
+            let tile = self.start_tile(route);
+            if !start_tiles.contains(&tile) {
+                start_tiles.push(tile);
+            }
+        }
+
+        match start_tiles.len() {
+            0 => Err(error),
+            1 => Ok(routes.swap_remove(0)),
+            _ => Err(RouteError::AmbiguousStart(start_tiles)),
+        }
     }
 
-    fn get_next_tile_coords(&self, prev_pos: (i32, i32), pos: (i32, i32)) -> Option<(i32, i32)> {
-        let current_tile = self.tiles[pos.0 as usize][pos.1 as usize];
+    fn walk(&self, mut prev_pos: (i32, i32), mut pos: (i32, i32)) -> Result<Route, RouteError> {
+        let tile = self.tile(pos).ok_or(RouteError::OutOfBounds(pos))?;
+        let mut result: Route = vec![(pos, tile)];
+
+        loop {
+            let next_pos = self.get_next_tile_coords(prev_pos, pos)?;
+            let next_tile = self.tile(next_pos).ok_or(RouteError::OutOfBounds(next_pos))?;
+            result.push((next_pos, next_tile));
+
+            if matches!(next_tile, Tile::Start) {
+                return Ok(result);
+            }
 
-        let candidate_pos = match &current_tile {
+            // pipes can only form a loop through S, but don't trust the input
+            if result.len() > self.tiles.len() * self.tiles[0].len() {
+                return Err(RouteError::NoLoop);
+            }
+
+            prev_pos = pos;
+            pos = next_pos;
+        }
+    }
+
+    fn get_next_tile_coords(&self, prev_pos: (i32, i32), pos: (i32, i32)) -> Result<(i32, i32), RouteError> {
+        let current_tile = self.tile(pos).ok_or(RouteError::OutOfBounds(pos))?;
+
+        let next_pos = match &current_tile {
             // |        north -> south                                      south -> north
-            Tile::NS => if prev_pos.0 < pos.0 { Some((pos.0+1, pos.1)) } else { Some((pos.0-1, pos.1))},
+            Tile::NS => if prev_pos.0 < pos.0 { (pos.0+1, pos.1) } else { (pos.0-1, pos.1) },
             // -        west -> east                                        east -> west
-            Tile::EW => if prev_pos.1 < pos.1 { Some((pos.0, pos.1+1)) } else { Some((pos.0, pos.1-1))},
+            Tile::EW => if prev_pos.1 < pos.1 { (pos.0, pos.1+1) } else { (pos.0, pos.1-1) },
             // L        north -> east                                       east -> north
-            Tile::NE => if prev_pos.0 < pos.0 { Some((pos.0, pos.1+1)) } else { Some((pos.0-1, pos.1))},
+            Tile::NE => if prev_pos.0 < pos.0 { (pos.0, pos.1+1) } else { (pos.0-1, pos.1) },
             // J        north -> west                                       west -> north
-            Tile::NW => if prev_pos.0 < pos.0 { Some((pos.0, pos.1-1)) } else { Some((pos.0-1, pos.1))},
+            Tile::NW => if prev_pos.0 < pos.0 { (pos.0, pos.1-1) } else { (pos.0-1, pos.1) },
             // 7        south -> west                                       west -> south
-            Tile::SW => if prev_pos.0 > pos.0 { Some((pos.0, pos.1-1)) } else { Some((pos.0+1, pos.1))},
+            Tile::SW => if prev_pos.0 > pos.0 { (pos.0, pos.1-1) } else { (pos.0+1, pos.1) },
             // F        south -> east                                       east -> south
-            Tile::SE => if prev_pos.0 > pos.0 { Some((pos.0, pos.1+1)) } else { Some((pos.0+1, pos.1))},
-            _ => None,
+            Tile::SE => if prev_pos.0 > pos.0 { (pos.0, pos.1+1) } else { (pos.0+1, pos.1) },
+            _ => return Err(RouteError::NoLoop),
         };
 
-        println!("candidate pos from {:?} {:?}: {:?}", current_tile, pos, candidate_pos);
+        if self.tile(next_pos).is_none() {
+            return Err(RouteError::OutOfBounds(next_pos));
+        }
+
+        if !self.can_enter(pos, next_pos) {
+            return Err(RouteError::NoLoop);
+        }
+
+        Ok(next_pos)
+    }
 
-        match candidate_pos {
-            Some(next_pos) if self.can_enter(pos, next_pos) => Some(next_pos),
-            _ => None,
+    fn tile(&self, pos: (i32, i32)) -> Option<Tile> {
+        if pos.0 < 0 || pos.1 < 0 {
+            return None;
         }
+
+        self.tiles.get(pos.0 as usize)?.get(pos.1 as usize).copied()
     }
 
     fn can_enter(&self, from: (i32, i32), to: (i32, i32)) -> bool {
-        if to.0 < 0 || to.1 < 0 || to.0 as usize >= self.tiles.len() || to.1 as usize >= self.tiles[0].len() {
-            // println!("position {:?} outside of map", to);
-            return false
+        match self.tile(to) {
+            Some(tile) => tile.can_enter_from_offset(from.0 - to.0, from.1 - to.1),
+            None => false,
         }
-        
-        // println!(
-        //     "trying to enter tile {:?} from {} {} to {} {}", 
-        //     self.tiles[to.0 as usize][to.1 as usize], from.0, from.1, to.0, to.1
-        // );
-        self.tiles[to.0 as usize][to.1 as usize].can_enter_from_offset(from.0 - to.0, from.1 - to.1)
     }
 
+    #[allow(dead_code)]
     fn print_around(&self, row_index: i32, col_index: i32) {
         println!();
         print!("   ");
@@ -217,11 +325,14 @@ impl Game {
 
         println!();
 
-        for (r, row) in self.tiles.iter().skip(row_index as usize -1).take(3).enumerate() {
-            print!("{:<3}", row_index + r as i32 - 1);
+        for row in row_index-1..row_index+2 {
+            print!("{:<3}", row);
 
-            for (c, tile) in row.iter().skip(col_index as usize - 1).take(3).enumerate() {
-                print!("{:>3}  ", tile);
+            for col in col_index-1..col_index+2 {
+                match self.tile((row, col)) {
+                    Some(tile) => print!("{:>3}  ", tile),
+                    None => print!("{:>3}  ", " "),
+                }
             }
 
             println!();
@@ -235,56 +346,88 @@ impl Tile {
             // |
             Tile::NS => col == 0 && (row == -1 || row == 1),
             // - 
-            Tile::EW => {
-                return row == 0 && (col == -1 || col == 1);
-            }
+            Tile::EW => row == 0 && (col == -1 || col == 1),
             // L 
-            Tile::NE => {
-                return (col == 0 && row == -1) || (col == 1 && row == 0);
-            }
+            Tile::NE => (col == 0 && row == -1) || (col == 1 && row == 0),
             // J
             Tile::NW => (col == 0 && row == -1) || (col == -1 && row == 0),
             // 7
-            Tile::SW => {
-                return (col == -1 && row == 0) || (col == 0 && row == 1);
-            }
+            Tile::SW => (col == -1 && row == 0) || (col == 0 && row == 1),
             // F
             Tile::SE => (col == 1 && row == 0) || (col == 0 && row == 1),
             Tile::Start => true,
             _ => false,
         }
     }
+
+    fn from_offsets(a: (i32, i32), b: (i32, i32)) -> Tile {
+        let mut offsets = [a, b];
+        offsets.sort();
+
+        match offsets {
+            [(-1, 0), (1, 0)] => Tile::NS,
+            [(0, -1), (0, 1)] => Tile::EW,
+            [(-1, 0), (0, 1)] => Tile::NE,
+            [(-1, 0), (0, -1)] => Tile::NW,
+            [(0, -1), (1, 0)] => Tile::SW,
+            [(0, 1), (1, 0)] => Tile::SE,
+            _ => Tile::Ground,
+        }
+    }
+
+    fn box_char(&self) -> char {
+        match &self {
+            Tile::NS => '│',
+            Tile::EW => '─',
+            Tile::NE => '└',
+            Tile::NW => '┘',
+            Tile::SW => '┐',
+            Tile::SE => '┌',
+            Tile::Start => 'S',
+            Tile::Ground => '.',
+        }
+    }
+
+    fn connects_north(&self) -> bool {
+        matches!(self, Tile::NS | Tile::NE | Tile::NW)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn route_tiles(route: &Route) -> Vec<Tile> {
+        route.iter().map(|(_, tile)| *tile).collect()
+    }
+
     #[test]
     fn test_game_get_route() {
         let game: Game = GAME_1.parse().unwrap();
-        let result = game.get_route();
-        assert_eq!("[SW, NS, NW, EW, NE, NS, Start]", format!("{:?}", result));
-        assert_eq!(4, game.route_farthest_point(result));
+        let result = game.get_route().unwrap();
+        assert_eq!("[EW, SW, NS, NW, EW, NE, NS, Start]", format!("{:?}", route_tiles(&result)));
+        assert_eq!(((1, 2), Tile::EW), result[0]);
+        assert_eq!(((1, 1), Tile::Start), result[7]);
+        assert_eq!(4, game.route_farthest_point(&result));
     }
 
     #[test]
     fn test_game_get_route_2() {
         let game: Game = GAME_2.parse().unwrap();
-        let result = game.get_route();
-        assert_eq!("[NS, SE, EW, SW, NE, SW, NW, SE, NW, EW, Start]", format!("{:?}", result));
-        assert_eq!(6, game.route_farthest_point(result));
+        let result = game.get_route().unwrap();
+        assert_eq!("[NS, NS, SE, EW, SW, NE, SW, NW, SE, NW, EW, Start]", format!("{:?}", route_tiles(&result)));
+        assert_eq!(6, game.route_farthest_point(&result));
     }
 
     #[test]
     fn test_game_walk() {
         let game: Game = GAME_1.parse().unwrap();
 
-        let mut result = game.walk((1, 1), (0, 1));
-        assert_eq!("[]", format!("{:?}", result));
+        assert_eq!(Err(RouteError::NoLoop), game.walk((1, 1), (0, 1)));
+        assert_eq!(Err(RouteError::OutOfBounds((-1, 1))), game.walk((1, 1), (-1, 1)));
 
-        let mut result = game.walk((1, 1), (1, 2));
-        assert_eq!("[SW, NS, NW, EW, NE, NS, Start]", format!("{:?}", result));
+        let result = game.walk((1, 1), (1, 2)).unwrap();
+        assert_eq!("[EW, SW, NS, NW, EW, NE, NS, Start]", format!("{:?}", route_tiles(&result)));
     }
 
     #[test]
@@ -299,46 +442,144 @@ mod tests {
     #[test]
     fn test_can_enter() {
         let game: Game = GAME_1.parse().unwrap();
-        assert_eq!(false, game.can_enter((1, 1), (0, 1)));
-        assert_eq!(false, game.can_enter((1, 1), (0, 0)));
-        assert_eq!(true, game.can_enter((1, 1), (1, 2)));
-        assert_eq!(true, game.can_enter((1, 1), (2, 1)));
+        assert!(!game.can_enter((1, 1), (0, 1)));
+        assert!(!game.can_enter((1, 1), (0, 0)));
+        assert!(game.can_enter((1, 1), (1, 2)));
+        assert!(game.can_enter((1, 1), (2, 1)));
     }
-}
 
-const GAME_1: &str = "
+    #[test]
+    fn test_start_tile() {
+        for (input, expected) in [
+            (GAME_1, Tile::SE),
+            (GAME_2, Tile::NE),
+            (GAME_3, Tile::NE),
+            (GAME_COMPLEX, Tile::SE),
+        ] {
+            let game: Game = input.parse().unwrap();
+            let route = game.get_route().unwrap();
+            assert_eq!(expected, game.start_tile(&route), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_game_get_route_errors() {
+        let game: Game = ".....\n.S...\n.....".parse().unwrap();
+        assert_eq!(Err(RouteError::NoLoop), game.get_route());
+
+        let game: Game = ".|...\n.S-7.\n.|.|.\n.L-J.".parse().unwrap();
+        assert_eq!(8, game.get_route().unwrap().len());
+
+        let game: Game = "S--\n|..".parse().unwrap();
+        let result = game.get_route();
+        assert_eq!(Err(RouteError::OutOfBounds((0, 3))), result);
+        assert_eq!("pipe at (0, 3) leads outside of the map", result.unwrap_err().to_string());
+
+        let game: Game = GAME_FIGURE_EIGHT.parse().unwrap();
+        assert_eq!(
+            Err(RouteError::AmbiguousStart(vec![Tile::NW, Tile::SE])),
+            game.get_route()
+        );
+    }
+
+    #[test]
+    fn test_enclosed_tiles() {
+        for (input, expected) in [
+            (GAME_1, 1),
+            (GAME_COMPLEX, 1),
+            (GAME_ENCLOSED, 4),
+            (GAME_ENCLOSED_SQUEEZE, 4),
+            (GAME_ENCLOSED_LARGE, 8),
+        ] {
+            let game: Game = input.parse().unwrap();
+            let route = game.get_route().unwrap();
+            assert_eq!(expected, game.enclosed_tiles(&route), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let game: Game = GAME_1.parse().unwrap();
+        let route = game.get_route().unwrap();
+        assert_eq!("·····\n·┌─┐·\n·│I│·\n·└─┘·\n·····\n", game.render(&route, false));
+
+        let game: Game = GAME_COMPLEX.parse().unwrap();
+        let route = game.get_route().unwrap();
+        assert_eq!(
+            "··┌┐·\n·┌┘│·\n┌┘I└┐\n│┌──┘\n└┘···\n",
+            game.render(&route, false)
+        );
+
+        let colored = game.render(&route, true);
+        assert!(colored.contains("\x1b[1;32mI\x1b[0m"));
+        assert!(colored.contains("\x1b[1;31m┌\x1b[0m"));
+    }
+
+    const GAME_1: &str = "
 .....
 .S-7.
 .|.|.
 .L-J.
 .....";
 
-const GAME_2: &str = "
+    const GAME_2: &str = "
 .F-7..
 .|.L7.
 .|.FJ.
 .S-J..";
 
-// const GAME_2: &str = "
-// ......
-// .F-7..
-// .|.|..
-// .|.L7.
-// .|..|.
-// .|..J.
-// .S-J..
-// ......";
-
-const GAME_3: &str = "
+    const GAME_3: &str = "
 .....
 .F-7.
 .|.|.
 .S-J.
 .....";
 
-const GAME_COMPLEX: &str = "
+    const GAME_COMPLEX: &str = "
 7-F7-
 .FJ|7
 SJLL7
 |F--J
-LJ.LJ";
\ No newline at end of file
+LJ.LJ";
+
+    const GAME_FIGURE_EIGHT: &str = "
+F-7..
+|.|..
+L-S-7
+..|.|
+..L-J";
+
+    const GAME_ENCLOSED: &str = "
+...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+
+    const GAME_ENCLOSED_SQUEEZE: &str = "
+..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+
+    const GAME_ENCLOSED_LARGE: &str = "
+.F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+}
@@ -3,7 +3,12 @@ use std::{collections::HashMap, fs};
 fn main() {
     let input = fs::read_to_string("./src/input.txt").expect("could not read file");
     let game = parse_game(&input);
-    println!("{}", game.sum_of_path_lengths())
+    for factor in [2, 1000000] {
+        match game.sum_of_path_lengths(factor) {
+            Ok(sum) => println!("{}", sum),
+            Err(e) => println!("{}", e),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -18,50 +23,92 @@ struct Game {
     galaxies: Vec<(usize, usize)>,
     empty_rows: Vec<usize>,
     empty_cols: Vec<usize>,
+    empty_rows_before: Vec<u64>,
+    empty_cols_before: Vec<u64>,
 }
 
 impl Game {
+    #[cfg(test)]
     fn galaxy_pairs(&self) -> Vec<((usize, usize), (usize, usize))> {
         let mut result: Vec<((usize, usize), (usize, usize))> = Vec::new();
 
         for (i, g1) in self.galaxies.iter().enumerate() {
-            for j in i + 1..self.galaxies.len() {
-                result.push((*g1, self.galaxies[j]));
+            for g2 in self.galaxies.iter().skip(i + 1) {
+                result.push((*g1, *g2));
             }
         }
 
         result
     }
 
-    fn sum_of_path_lengths(&self) -> u64 {
-        self.galaxy_pairs().iter().map(|(a, b)| self.path_len(*a, *b)).sum()
+    // sum of |a - b| over all pairs: once sorted, every coordinate is subtracted
+    // from all the ones after it, so the sum is coord * index - sum of previous coords
+    fn sum_of_path_lengths(&self, factor: u64) -> Result<u64, String> {
+        check_factor(factor)?;
+        let galaxies = self.expanded_galaxies(factor);
+        let mut rows: Vec<u64> = galaxies.iter().map(|g| g.0).collect();
+        let mut cols: Vec<u64> = galaxies.iter().map(|g| g.1).collect();
+
+        Ok(sum_of_distances(&mut rows) + sum_of_distances(&mut cols))
     }
 
-    fn path_len(&self, a: (usize, usize), b: (usize, usize)) -> u64 {
-        let mut len = a.0.abs_diff(b.0) as u64 + a.1.abs_diff(b.1) as u64;
+    #[cfg(test)]
+    fn path_len(&self, a: (usize, usize), b: (usize, usize), factor: u64) -> Result<u64, String> {
+        check_factor(factor)?;
+        let a = self.expand(a, factor);
+        let b = self.expand(b, factor);
 
-        let mut row = 0;
-        for i in 0..a.0.abs_diff(b.0) {
-            row = if a.0 > b.0 { a.0 - i } else { a.0 + i };
+        Ok(a.0.abs_diff(b.0) + a.1.abs_diff(b.1))
+    }
 
-            if self.empty_rows.contains(&row) {
-                len += 1000000 - 1;
-            }
-        }
+    // every empty row or column before a galaxy is replaced by `factor` of them,
+    // the factor has been checked to be at least one
+    fn expanded_galaxies(&self, factor: u64) -> Vec<(u64, u64)> {
+        self.galaxies.iter().map(|g| self.expand(*g, factor)).collect()
+    }
 
-        let mut col = 0;
-        for i in 0..a.1.abs_diff(b.1) {
-            col = if a.1 > b.1 { a.1 - i } else { a.1 + i };
-            
-            if self.empty_cols.contains(&col) {
-                len += 1000000 - 1;
-            }
-        }
+    fn expand(&self, pos: (usize, usize), factor: u64) -> (u64, u64) {
+        (
+            pos.0 as u64 + self.empty_rows_before[pos.0] * (factor - 1),
+            pos.1 as u64 + self.empty_cols_before[pos.1] * (factor - 1),
+        )
+    }
+}
+
+fn check_factor(factor: u64) -> Result<(), String> {
+    if factor == 0 {
+        return Err("an empty row or column can't expand to nothing, factor is 0".to_string());
+    }
+    Ok(())
+}
+
+fn sum_of_distances(coords: &mut [u64]) -> u64 {
+    coords.sort();
+
+    let mut prefix = 0;
+    let mut result = 0;
+
+    for (i, c) in coords.iter().enumerate() {
+        result += c * i as u64 - prefix;
+        prefix += c;
+    }
 
-        // println!("{:?} {:?}: {}, original {}", a, b, len, a.0.abs_diff(b.0) + a.1.abs_diff(b.1));
+    result
+}
 
-        len
+// number of empty lines strictly before each index
+fn prefix_counts(empty: &[usize], len: usize) -> Vec<u64> {
+    let mut is_empty = vec![false; len];
+    for i in empty.iter() {
+        is_empty[*i] = true;
     }
+
+    let mut result = vec![0; len + 1];
+    for i in 0..len {
+        result[i + 1] = result[i] + is_empty[i] as u64;
+    }
+
+    result
 }
 
 fn parse_game(s: &str) -> Game {
@@ -70,11 +117,13 @@ fn parse_game(s: &str) -> Game {
         galaxies: Vec::new(),
         empty_rows: Vec::new(),
         empty_cols: Vec::new(),
+        empty_rows_before: Vec::new(),
+        empty_cols_before: Vec::new(),
     };
 
     let mut galaxies_in_columns: HashMap<usize, usize> = HashMap::new();
 
-    for (row_index, line) in s.trim().lines().into_iter().enumerate() {
+    for (row_index, line) in s.trim().lines().enumerate() {
         let cells: Vec<Cell> = line
             .trim()
             .split("")
@@ -107,6 +156,9 @@ fn parse_game(s: &str) -> Game {
         }
     }
 
+    game.empty_rows_before = prefix_counts(&game.empty_rows, game.cells.len());
+    game.empty_cols_before = prefix_counts(&game.empty_cols, galaxies_in_columns.len());
+
     game
 }
 
@@ -131,16 +183,42 @@ mod tests {
     #[test]
     fn test_path_len() {
         let game = parse_game(GAME);
-        assert_eq!(15, game.path_len(game.galaxies[0], game.galaxies[6]));
-        assert_eq!(17, game.path_len(game.galaxies[2], game.galaxies[5]));
-        assert_eq!(5, game.path_len(game.galaxies[7], game.galaxies[8]));
-        assert_eq!(9, game.path_len(game.galaxies[4], game.galaxies[8]));
+        assert_eq!(Ok(15), game.path_len(game.galaxies[0], game.galaxies[6], 2));
+        assert_eq!(Ok(17), game.path_len(game.galaxies[2], game.galaxies[5], 2));
+        assert_eq!(Ok(5), game.path_len(game.galaxies[7], game.galaxies[8], 2));
+        assert_eq!(Ok(9), game.path_len(game.galaxies[4], game.galaxies[8], 2));
+
+        assert_eq!(Ok(374), game.sum_of_path_lengths(2));
+        assert_eq!(Ok(1030), game.sum_of_path_lengths(10));
+        assert_eq!(Ok(8410), game.sum_of_path_lengths(100));
+    }
 
-        assert_eq!(374, game.sum_of_path_lengths());
+    #[test]
+    fn test_sum_of_path_lengths_matches_pairs() {
+        let game = parse_game(GAME);
+
+        for factor in [1, 2, 10, 1000000] {
+            let pairwise: Result<u64, String> = game
+                .galaxy_pairs()
+                .iter()
+                .map(|(a, b)| game.path_len(*a, *b, factor))
+                .sum();
+            assert_eq!(pairwise, game.sum_of_path_lengths(factor));
+        }
+    }
+
+    #[test]
+    fn test_sum_of_path_lengths_zero_factor() {
+        let game = parse_game(GAME);
+        let err = game.sum_of_path_lengths(0).unwrap_err();
+        assert!(err.contains("can't expand to nothing"), "{}", err);
+        assert_eq!(
+            Err(err),
+            game.path_len(game.galaxies[0], game.galaxies[1], 0)
+        );
     }
-}
 
-const GAME: &str = "
+    const GAME: &str = "
 ...#......
 .......#..
 #.........
@@ -151,3 +229,4 @@ const GAME: &str = "
 ..........
 .......#..
 #...#.....";
+}
@@ -1,11 +1,32 @@
-use std::{collections::HashSet, fs};
+use std::{collections::{HashMap, HashSet}, env, fmt::{self, Display}, fs, thread, time::Duration};
 
 fn main() {
     let input = fs::read_to_string("./src/input.txt").expect("could not read file");
     let grid = grid_from_str(&input);
 
+    // cargo run -- --render | --arrows | --animate
+    let args: Vec<String> = env::args().collect();
+    if args.iter().any(|a| a == "--render" || a == "--arrows") {
+        let energized = grid.trace_from(0, 0, Direction::Right);
+        print!("{}", grid.render(&energized, args.iter().any(|a| a == "--arrows")));
+    }
+    if args.iter().any(|a| a == "--animate") {
+        grid.animate(0, 0, Direction::Right, Duration::from_millis(30));
+    }
+
     println!("Energized from 0, 0, Right: {}", grid.energize_from(0, 0, Direction::Right));
-    println!("Energized max: {}", grid.energize_max());
+
+    // cargo run -- --parallel traces every edge entry on its own instead of the memoized segments
+    let (light, count) = if args.iter().any(|a| a == "--parallel") {
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        grid.energize_max_parallel(threads)
+    } else {
+        grid.energize_max()
+    };
+    println!(
+        "Energized max: {} from {} {} {:?}",
+        count, light.row, light.col, light.direction
+    );
 }
 
 fn grid_from_str(s: &str) -> Grid {
@@ -21,7 +42,7 @@ fn grid_from_str(s: &str) -> Grid {
     }
 
     Grid {
-        tiles: tiles
+        tiles
     }
 }
 
@@ -63,7 +84,51 @@ enum Direction {
     Right
 }
 
-#[derive(Debug, Clone, Copy)]
+impl Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            Kind::Empty => write!(f, "."),
+            Kind::VertSplitter => write!(f, "|"),
+            Kind::HorSplitter => write!(f, "-"),
+            Kind::ForwardMirror => write!(f, "/"),
+            Kind::BackMirror => write!(f, "\\"),
+        }
+    }
+}
+
+impl Direction {
+    fn arrow(&self) -> char {
+        match self {
+            Direction::Top => '^',
+            Direction::Bottom => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        }
+    }
+}
+
+// energized tiles and the directions of the beams that went through each of them
+#[derive(Debug, Default)]
+struct Energized {
+    tiles: HashSet<(usize, usize)>,
+    directions: HashMap<(usize, usize), Vec<Direction>>,
+}
+
+impl Energized {
+    // returns false if a beam already went through the tile in this direction
+    fn add(&mut self, light: Light) -> bool {
+        let directions = self.directions.entry((light.row, light.col)).or_default();
+        if directions.contains(&light.direction) {
+            return false;
+        }
+
+        directions.push(light.direction);
+        self.tiles.insert((light.row, light.col));
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Light {
     row: usize,
     col: usize,
@@ -71,74 +136,209 @@ struct Light {
 }
 
 impl Grid {
-    fn energize_max(&self) -> usize {
-        let mut values:Vec<usize> = Vec::new();
-
-        for row in 0..self.tiles.len() {
-            for col in 0..self.tiles[0].len() {
-                if row == 0 && col == 0 {
-                    values.push(self.energize_from(row, col, Direction::Right));
-                    values.push(self.energize_from(row, col, Direction::Bottom));
-                } else if row == self.tiles.len()-1 && col == self.tiles[0].len() - 1 {
-                    values.push(self.energize_from(row, col, Direction::Top));
-                    values.push(self.energize_from(row, col, Direction::Left));
-                } else if row == 0 {
-                    values.push(self.energize_from(row, col, Direction::Bottom));
-                } else if col == 0 {
-                    values.push(self.energize_from(row, col, Direction::Right));
-                } else if row == self.tiles.len()-1 {
-                    values.push(self.energize_from(row, col, Direction::Top));
-                } else if col == self.tiles[0].len()-1 {
-                    values.push(self.energize_from(row, col, Direction::Left));
-                }
-            }
+    fn energize_max(&self) -> (Light, usize) {
+        best_entry(self.energize_edges())
+    }
+
+    // traces every edge entry on its own, split between threads
+    fn energize_max_parallel(&self, threads: usize) -> (Light, usize) {
+        let entries = self.edge_entries();
+        let chunk_size = entries.len().div_ceil(threads.max(1));
+
+        let results: Vec<(Light, usize)> = thread::scope(|scope| {
+            let handles: Vec<_> = entries
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|l| (*l, self.energize_from(l.row, l.col, l.direction)))
+                            .collect::<Vec<(Light, usize)>>()
+                    })
+                })
+                .collect();
+
+            // joined in the order of the chunks, so the result doesn't depend on timing
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("energizing thread panicked"))
+                .collect()
+        });
+
+        best_entry(results)
+    }
+
+    // every tile on the border with the beam entering it from outside of the grid
+    fn edge_entries(&self) -> Vec<Light> {
+        let rows = self.tiles.len();
+        let cols = self.tiles[0].len();
+        let mut result = Vec::new();
+
+        for col in 0..cols {
+            result.push(Light { row: 0, col, direction: Direction::Bottom });
+            result.push(Light { row: rows - 1, col, direction: Direction::Top });
+        }
+
+        for row in 0..rows {
+            result.push(Light { row, col: 0, direction: Direction::Right });
+            result.push(Light { row, col: cols - 1, direction: Direction::Left });
         }
 
-        *values.iter().max().unwrap()
+        result
     }
-    
-    fn energize_from(&self, row: usize, col: usize, dir: Direction) -> usize {
-        let mut energized_count = 0;
-        let mut lights:Vec<Light> = Vec::new();
-        lights.push(Light{
-            row: row, col: col, direction: dir,
+
+    // The beam is split into straight segments, each ending on a mirror, a splitter
+    // it doesn't pass through, or the edge of the grid. Segments feeding each other in
+    // a loop are collapsed into one component, so the energized tiles of a component are
+    // its own tiles plus those of the components it leads to, computed once for all entries.
+    fn energize_edges(&self) -> Vec<(Light, usize)> {
+        let entries = self.edge_entries();
+        let mut ids: HashMap<(usize, usize, Direction), usize> = HashMap::new();
+        let mut segments: Vec<Vec<(usize, usize)>> = Vec::new();
+        let mut successors: Vec<Vec<usize>> = Vec::new();
+        let mut queue: Vec<Light> = Vec::new();
+
+        let mut segment_id = |light: Light, queue: &mut Vec<Light>| -> usize {
+            let key = (light.row, light.col, light.direction);
+            let next_id = ids.len();
+            *ids.entry(key).or_insert_with(|| {
+                queue.push(light);
+                next_id
+            })
+        };
+
+        let entry_ids: Vec<usize> = entries.iter().map(|l| segment_id(*l, &mut queue)).collect();
+
+        while let Some(light) = queue.pop() {
+            let id = segment_id(light, &mut queue);
+            let (tiles, next) = self.trace_segment(light);
+
+            if segments.len() <= id {
+                segments.resize(id + 1, Vec::new());
+                successors.resize(id + 1, Vec::new());
+            }
+
+            segments[id] = tiles;
+            successors[id] = next.iter().map(|l| segment_id(*l, &mut queue)).collect();
+        }
+
+        let width = self.tiles[0].len();
+        let energized = condense(&successors, |id, bits: &mut Vec<u64>| {
+            if bits.is_empty() {
+                bits.resize((self.tiles.len() * width).div_ceil(64), 0);
+            }
+            for (row, col) in segments[id].iter() {
+                let i = row * width + col;
+                bits[i / 64] |= 1 << (i % 64);
+            }
         });
 
-        println!("energizing from {} {} {:?}", row, col, dir);
+        entries
+            .into_iter()
+            .zip(entry_ids)
+            .map(|(light, id)| {
+                let count = energized[id].iter().map(|b| b.count_ones() as usize).sum();
+                (light, count)
+            })
+            .collect()
+    }
 
-        let mut visited: HashSet<(usize, usize, Direction)> = HashSet::new();
-        let mut energized: HashSet<(usize, usize)> = HashSet::new();
-        let mut iters = 0;
+    // follows the beam in a straight line, returns the tiles it covers
+    // and the beams leaving the tile where it turns or splits
+    fn trace_segment(&self, mut light: Light) -> (Vec<(usize, usize)>, Vec<Light>) {
+        let mut tiles = Vec::new();
 
-        while let Some(light) = lights.pop() {
-            iters += 1;
+        loop {
+            tiles.push((light.row, light.col));
+            let next = self.next(light);
 
-            if iters > 100000 {
-                panic!("too long");
+            match next[..] {
+                [straight] if straight.direction == light.direction => light = straight,
+                _ => return (tiles, next),
             }
+        }
+    }
 
-            if !energized.contains(&(light.row, light.col)) {
-                energized.insert((light.row, light.col));
-                energized_count += 1;
-            }
-            
-            for next_light in  self.next(light) {
-                if visited.contains(&(next_light.row, next_light.col, next_light.direction)) {
-                    continue;
+    fn energize_from(&self, row: usize, col: usize, dir: Direction) -> usize {
+        self.trace_from(row, col, dir).tiles.len()
+    }
+
+    fn trace_from(&self, row: usize, col: usize, dir: Direction) -> Energized {
+        let mut energized = Energized::default();
+        let mut lights = vec![Light { row, col, direction: dir }];
+        energized.add(lights[0]);
+
+        while !lights.is_empty() {
+            lights = self.step(&lights, &mut energized);
+        }
+
+        energized
+    }
+
+    // moves every beam head one tile further, beams that were already seen
+    // on a tile in the same direction are dropped
+    fn step(&self, lights: &[Light], energized: &mut Energized) -> Vec<Light> {
+        let mut result = Vec::new();
+
+        for light in lights.iter() {
+            for next_light in self.next(*light) {
+                if energized.add(next_light) {
+                    result.push(next_light);
                 }
+            }
+        }
+
+        result
+    }
 
-                visited.insert((next_light.row, next_light.col, next_light.direction));
-                // println!("total: {}, energized: {}, next light: {:?}", lights.len(), energized_count, next_light);
-                lights.push(next_light);
+    // energized tiles as #, or with arrows the way the beams go through empty tiles
+    // (a number if several beams cross it) and mirrors or splitters as they are
+    fn render(&self, energized: &Energized, arrows: bool) -> String {
+        let mut result = String::new();
+
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                let directions = energized.directions.get(&(row, col));
+
+                let c = match (directions, arrows) {
+                    (None, false) => '.',
+                    (Some(_), false) => '#',
+                    (None, true) => tile.to_string().chars().next().unwrap(),
+                    (Some(_), true) if !matches!(tile.kind, Kind::Empty) => tile.to_string().chars().next().unwrap(),
+                    (Some(d), true) if d.len() == 1 => d[0].arrow(),
+                    (Some(d), true) => char::from_digit(d.len() as u32, 10).unwrap(),
+                };
+
+                result.push(c);
             }
+
+            result.push('\n');
         }
 
-        energized_count
+        result
+    }
+
+    // redraws the grid after every step of the beams
+    fn animate(&self, row: usize, col: usize, dir: Direction, delay: Duration) {
+        let mut energized = Energized::default();
+        let mut lights = vec![Light { row, col, direction: dir }];
+        energized.add(lights[0]);
+        let mut frame = 0;
+
+        while !lights.is_empty() {
+            print!("\x1b[2J\x1b[H");
+            print!("{}", self.render(&energized, true));
+            println!("step {}, energized {}", frame, energized.tiles.len());
+            thread::sleep(delay);
+
+            lights = self.step(&lights, &mut energized);
+            frame += 1;
+        }
     }
 
     fn next(&self, light: Light) -> Vec<Light> {
         let tile = self.tiles[light.row][light.col];
-        let mut next1: Option<Light> = None;
+        let next1: Option<Light>;
         let mut next2: Option<Light> = None;
         let mut result = Vec::new();
 
@@ -172,12 +372,12 @@ impl Grid {
             },
         }
 
-        if next1.is_some() {
-            result.push(next1.unwrap());
+        if let Some(light) = next1 {
+            result.push(light);
         }
 
-        if next2.is_some() {
-            result.push(next2.unwrap());
+        if let Some(light) = next2 {
+            result.push(light);
         }
 
         result
@@ -193,10 +393,121 @@ impl Grid {
     }
 }
 
+// the first entry with the most energized tiles, so ties are resolved the same way every time
+fn best_entry(results: Vec<(Light, usize)>) -> (Light, usize) {
+    let mut best = results[0];
+
+    for result in results.into_iter().skip(1) {
+        if result.1 > best.1 {
+            best = result;
+        }
+    }
+
+    best
+}
+
+// Tarjan's strongly connected components. A component is finished only after every
+// component reachable from it, so its bitset can be merged from theirs right away.
+// `fill` sets the bits of a single segment. Returns the merged bitset of every node.
+fn condense(successors: &[Vec<usize>], fill: impl Fn(usize, &mut Vec<u64>)) -> Vec<Vec<u64>> {
+    let n = successors.len();
+    let mut index = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut component = vec![usize::MAX; n];
+    let mut components: Vec<Vec<u64>> = Vec::new();
+    let mut counter = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+
+        // explicit call stack of (node, next successor to visit), the recursion is too deep
+        let mut calls: Vec<(usize, usize)> = vec![(root, 0)];
+        index[root] = counter;
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((node, i)) = calls.last_mut() {
+            let node = *node;
+
+            if let Some(next) = successors[node].get(*i).copied() {
+                *i += 1;
+
+                if index[next] == usize::MAX {
+                    index[next] = counter;
+                    low[next] = counter;
+                    counter += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    calls.push((next, 0));
+                } else if on_stack[next] {
+                    low[node] = low[node].min(index[next]);
+                }
+
+                continue;
+            }
+
+            calls.pop();
+            if let Some((parent, _)) = calls.last() {
+                low[*parent] = low[*parent].min(low[node]);
+            }
+
+            if low[node] != index[node] {
+                continue;
+            }
+
+            let mut members = Vec::new();
+            while let Some(member) = stack.pop() {
+                on_stack[member] = false;
+                component[member] = components.len();
+                members.push(member);
+                if member == node {
+                    break;
+                }
+            }
+
+            let mut bits: Vec<u64> = Vec::new();
+            for member in members.iter() {
+                fill(*member, &mut bits);
+
+                for next in successors[*member].iter() {
+                    let c = component[*next];
+                    if c == components.len() {
+                        continue;
+                    }
+                    for (b, other) in bits.iter_mut().zip(components[c].iter()) {
+                        *b |= other;
+                    }
+                }
+            }
+
+            components.push(bits);
+        }
+    }
+
+    component.iter().map(|c| components[*c].clone()).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE: &str = r".|...\....
+        |.-.\.....
+        .....|-...
+        ........|.
+        ..........
+        .........\
+        ..../.\\..
+        .-.-/..|..
+        .|....-|.\
+        ..//.|....";
+
     #[test]
     fn test_energize() {
         assert_eq!(3, grid_from_str(r"...").energize_from(0, 0, Direction::Right));
@@ -205,7 +516,7 @@ mod tests {
         assert_eq!(2, grid_from_str(r".\.").energize_from(0, 0, Direction::Right));
         assert_eq!(2, grid_from_str(r"./.").energize_from(0, 0, Direction::Right));
 
-        let mut grid = grid_from_str(
+        let grid = grid_from_str(
             r".\.
               ...
               ./.
@@ -213,13 +524,13 @@ mod tests {
         );
         assert_eq!(5, grid.energize_from(0, 0, Direction::Right));
 
-        let mut grid = grid_from_str(
+        let grid = grid_from_str(
             r"..\.
               ..-."
         );
         assert_eq!(7, grid.energize_from(0, 0, Direction::Right));
 
-        let mut grid = grid_from_str(
+        let grid = grid_from_str(
             r"...\.
               /..||
               \../.
@@ -228,9 +539,71 @@ mod tests {
         assert_eq!(12, grid.energize_from(0, 0, Direction::Right));
     }
 
+    #[test]
+    fn test_render() {
+        let grid: Grid = grid_from_str(EXAMPLE);
+        let energized = grid.trace_from(0, 0, Direction::Right);
+        assert_eq!(46, energized.tiles.len());
+        assert_eq!(vec![Direction::Right], energized.directions[&(0, 0)]);
+
+        assert_eq!(
+            "######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#..
+",
+            grid.render(&energized, false)
+        );
+
+        assert_eq!(
+            ">|<<<\\....
+|v-.\\^....
+.v...|->>>
+.v...v^.|.
+.v...v^...
+.v...v^..\\
+.v../2\\\\..
+<->-/vv|..
+.|<<<2-|.\\
+.v//.|.v..
+",
+            grid.render(&energized, true)
+        );
+    }
+
+    #[test]
+    fn test_step() {
+        let grid: Grid = grid_from_str(r"..\.
+              ..-.");
+        let mut energized = Energized::default();
+        let start = Light { row: 0, col: 0, direction: Direction::Right };
+        energized.add(start);
+
+        let lights = grid.step(&[start], &mut energized);
+        assert_eq!(1, lights.len());
+        let lights = grid.step(&lights, &mut energized);
+        assert_eq!((0, 2, Direction::Right), (lights[0].row, lights[0].col, lights[0].direction));
+        let lights = grid.step(&lights, &mut energized);
+        assert_eq!((1, 2, Direction::Bottom), (lights[0].row, lights[0].col, lights[0].direction));
+        let lights = grid.step(&lights, &mut energized);
+        assert_eq!(2, lights.len());
+        assert_eq!(6, energized.tiles.len());
+
+        let lights = grid.step(&lights, &mut energized);
+        assert_eq!(1, lights.len());
+        assert_eq!(7, energized.tiles.len());
+        assert!(grid.step(&lights, &mut energized).is_empty());
+    }
+
     #[test]
     fn test_energize_2() {
-        let mut grid: Grid = grid_from_str(r".|...\....
+        let grid: Grid = grid_from_str(r".|...\....
         |.-.\.....
         .....|-...
         ........|.
@@ -255,6 +628,78 @@ mod tests {
         .-.-/..|..
         .|....-|.\
         ..//.|....");
-        assert_eq!(51, grid.energize_max());
+        let (light, count) = grid.energize_max();
+        assert_eq!(51, count);
+        assert_eq!(Light { row: 0, col: 3, direction: Direction::Bottom }, light);
+        assert_eq!((light, count), grid.energize_max_parallel(1));
+        assert_eq!((light, count), grid.energize_max_parallel(3));
+        assert_eq!((light, count), grid.energize_max_parallel(100));
+
+        for (light, count) in grid.energize_edges() {
+            assert_eq!(
+                grid.energize_from(light.row, light.col, light.direction),
+                count,
+                "{:?}",
+                light
+            );
+        }
+    }
+
+    #[test]
+    fn test_energize_edges_loop() {
+        // the beam from the left runs around the mirrors forever
+        let grid: Grid = grid_from_str(r"..\.\
+        ./..-
+        .\../");
+        let result = grid.energize_edges();
+        assert_eq!(2 * 3 + 2 * 5, result.len());
+
+        for (light, count) in result {
+            assert_eq!(
+                grid.energize_from(light.row, light.col, light.direction),
+                count,
+                "{:?}",
+                light
+            );
+        }
     }
-}
\ No newline at end of file
+
+    // cargo test --release bench_energize_max -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_energize_max() {
+        // pseudo-random 110x110 contraption, about one mirror or splitter in ten tiles
+        let mut seed: u64 = 16;
+        let mut input = String::new();
+        for _ in 0..110 {
+            for _ in 0..110 {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                input.push(match (seed >> 33) % 40 {
+                    0 => '/',
+                    1 => '\\',
+                    2 => '|',
+                    3 => '-',
+                    _ => '.',
+                });
+            }
+            input.push('\n');
+        }
+        let grid = grid_from_str(input.trim());
+
+        let started = std::time::Instant::now();
+        let sequential = grid.energize_max_parallel(1);
+        println!("sequential: {:?}", started.elapsed());
+
+        let threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let started = std::time::Instant::now();
+        let parallel = grid.energize_max_parallel(threads);
+        println!("parallel, {} threads: {:?}", threads, started.elapsed());
+
+        let started = std::time::Instant::now();
+        let memoized = grid.energize_max();
+        println!("memoized: {:?}", started.elapsed());
+
+        assert_eq!(sequential, parallel);
+        assert_eq!(sequential, memoized);
+    }
+}
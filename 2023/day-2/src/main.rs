@@ -1,18 +1,71 @@
-use std::{str::FromStr, env, fs};
+use std::{collections::BTreeMap, env, fmt, fs, str::FromStr};
+
+const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
 
 fn main() {
+    // cargo run -- src/input.txt [--bag "12 red, 13 green, 14 blue" | --bag-file bag.txt]
+    //     [--possible] [--min-bag] [--max-per-game]
     let args: Vec<String> = env::args().collect();
     let path = args
         .get(1)
         .expect("path is required: cargo run -- src/input.txt");
     let input = fs::read_to_string(path).expect("could not read file");
     let games = parse_games(input.as_str());
-    let ids = ids_of_possible_games(&games, 12, 13, 14);
-    println!("Sum of IDs of possible games: {}", ids.iter().sum::<u32>());
 
-    let powers_sum: u32 = games.iter().
-        map(|game| game.min_set_of_cubes().power()).sum();
-    println!("Sum of the powers of minimum sets of cubes: {}", powers_sum);
+    let option = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+    };
+    let bag_str = match (option("--bag"), option("--bag-file")) {
+        (Some(bag), _) => bag.clone(),
+        // one colour per line or comma separated, both work
+        (None, Some(file)) => fs::read_to_string(file)
+            .expect("could not read bag file")
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<&str>>()
+            .join(","),
+        (None, None) => DEFAULT_BAG.to_string(),
+    };
+    let bag = match CubeSet::from_str(&bag_str) {
+        Ok(bag) => bag,
+        Err(_) => {
+            println!("could not parse bag: {}", bag_str);
+            return;
+        }
+    };
+
+    let flag = |name: &str| args.iter().any(|a| a == name);
+    let queries = ["--possible", "--min-bag", "--max-per-game"];
+    if !queries.iter().any(|q| flag(q)) {
+        let ids = ids_of_possible_games(&games, &bag);
+        println!("Sum of IDs of possible games: {}", ids.iter().sum::<u32>());
+
+        let powers_sum: u32 = games.iter().
+            map(|game| game.min_set_of_cubes().power(&bag)).sum();
+        println!("Sum of the powers of minimum sets of cubes: {}", powers_sum);
+        return;
+    }
+
+    if flag("--possible") {
+        let ids: Vec<String> = ids_of_possible_games(&games, &bag)
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        println!("Possible with {}: {}", bag, ids.join(", "));
+    }
+
+    if flag("--min-bag") {
+        println!("Minimum bag for all games: {}", min_bag(&games));
+    }
+
+    if flag("--max-per-game") {
+        for game in games.iter() {
+            println!("Game {}: {}", game.id, game.min_set_of_cubes());
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -24,38 +77,75 @@ struct Game {
     rounds: Vec<CubeSet>
 }
 
-#[derive(Debug)]
+// counts by colour, colours that aren't there count as zero
+#[derive(Debug, Clone, Default, PartialEq)]
 struct CubeSet {
-    blue: u32,
-    red: u32,
-    green: u32
+    cubes: BTreeMap<String, u32>,
 }
 
 fn parse_games(s: &str) -> Vec<Game> {
     s.lines().map(|line| Game::from_str(line).unwrap()).collect()
 }
 
-fn ids_of_possible_games(games: &Vec<Game>, red: u32, green: u32, blue: u32) -> Vec<u32> {
-    games.iter().filter(|game| {
-        game.rounds.iter().all(|round| {
-            round.blue <= blue && round.green <= green && round.red <= red
-        })
-    }).map(|game| game.id).collect()
+fn ids_of_possible_games(games: &[Game], bag: &CubeSet) -> Vec<u32> {
+    games
+        .iter()
+        .filter(|game| game.rounds.iter().all(|round| round.fits_in(bag)))
+        .map(|game| game.id)
+        .collect()
+}
+
+// the smallest bag every game is possible with
+fn min_bag(games: &[Game]) -> CubeSet {
+    games
+        .iter()
+        .map(|game| game.min_set_of_cubes())
+        .fold(CubeSet::default(), |bag, set| bag.max(&set))
 }
 
 impl Game {
     fn min_set_of_cubes(&self) -> CubeSet {
-        CubeSet{
-            blue: self.rounds.iter().map(|r| r.blue).max().unwrap(), 
-            green: self.rounds.iter().map(|r| r.green).max().unwrap(), 
-            red: self.rounds.iter().map(|r| r.red).max().unwrap()
-        }
+        self.rounds
+            .iter()
+            .fold(CubeSet::default(), |set, round| set.max(round))
     }
 }
 
 impl CubeSet {
-    fn power(&self) -> u32 {
-        self.blue * self.red * self.green
+    fn get(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    // product over the colours of the bag, a colour the set doesn't have makes it 0
+    fn power(&self, bag: &CubeSet) -> u32 {
+        bag.cubes.keys().map(|color| self.get(color)).product()
+    }
+
+    fn fits_in(&self, bag: &CubeSet) -> bool {
+        self.cubes
+            .iter()
+            .all(|(color, count)| *count <= bag.get(color))
+    }
+
+    // per colour maximum of both sets
+    fn max(&self, other: &CubeSet) -> CubeSet {
+        let mut result = self.clone();
+        for (color, count) in other.cubes.iter() {
+            let entry = result.cubes.entry(color.clone()).or_insert(0);
+            *entry = (*entry).max(*count);
+        }
+        result
+    }
+}
+
+impl fmt::Display for CubeSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cubes: Vec<String> = self
+            .cubes
+            .iter()
+            .map(|(color, count)| format!("{} {}", count, color))
+            .collect();
+        write!(f, "{}", cubes.join(", "))
     }
 }
 
@@ -63,7 +153,7 @@ impl FromStr for CubeSet {
     type Err = ParseError;
     
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut round = CubeSet{blue: 0, red: 0, green: 0};
+        let mut round = CubeSet::default();
 
         for color_str  in s.split(",") {
             let mut count_and_color = color_str.trim().split(" ");
@@ -71,10 +161,10 @@ impl FromStr for CubeSet {
                 ok_or(ParseError)?.
                 parse::<u32>().map_err(|_| ParseError)?;
 
-            match count_and_color.next() {
-                Some("blue") => round.blue = count,
-                Some("green") => round.green = count,
-                Some("red") => round.red = count,
+            match (count_and_color.next(), count_and_color.next()) {
+                (Some(color), None) => {
+                    round.cubes.insert(color.to_string(), count);
+                }
                 _ => return Err(ParseError),
             }
         }
@@ -93,7 +183,7 @@ impl FromStr for Game {
             trim_start_matches("Game ").
             parse::<u32>().map_err(|_| ParseError)?;
 
-        let mut game = Game { id: id, rounds: Vec::new() };
+        let mut game = Game { id, rounds: Vec::new() };
 
         for round_str in parts.next().ok_or(ParseError)?.split(";") {
             game.rounds.push(CubeSet::from_str(round_str)?);
@@ -107,40 +197,98 @@ impl FromStr for Game {
 mod tests {
     use super::*;
 
+    const GAMES: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
     #[test]
     fn test_game_from_str() {
         let game = Game::from_str("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
         assert_eq!(1, game.id);
         assert_eq!(3, game.rounds.len());
-        assert_eq!(3, game.rounds[0].blue);
-        assert_eq!(4, game.rounds[0].red);
-        assert_eq!(0, game.rounds[0].green);
-        assert_eq!(6, game.rounds[1].blue);
-        assert_eq!(1, game.rounds[1].red);
-        assert_eq!(2, game.rounds[1].green);
+        assert_eq!(3, game.rounds[0].get("blue"));
+        assert_eq!(4, game.rounds[0].get("red"));
+        assert_eq!(0, game.rounds[0].get("green"));
+        assert_eq!(6, game.rounds[1].get("blue"));
+        assert_eq!(1, game.rounds[1].get("red"));
+        assert_eq!(2, game.rounds[1].get("green"));
 
         let min_set = game.min_set_of_cubes();
-        assert_eq!(4, min_set.red);
-        assert_eq!(2, min_set.green);
-        assert_eq!(6, min_set.blue);
+        assert_eq!(4, min_set.get("red"));
+        assert_eq!(2, min_set.get("green"));
+        assert_eq!(6, min_set.get("blue"));
 
-        assert_eq!(48, min_set.power());
+        assert_eq!(48, min_set.power(&CubeSet::from_str(DEFAULT_BAG).unwrap()));
     }
 
     #[test]
     fn test_game_from_str_invalid() {
         assert!(Game::from_str("Game abc: def").is_err());
-        assert!(Game::from_str("Game 123: 3 blue, 1 orange, 1 green").is_err());
+        assert!(Game::from_str("Game 123: 3 blue, x orange, 1 green").is_err());
+        assert!(Game::from_str("Game 123: 3 blue, 1 dark orange").is_err());
+        assert!(Game::from_str("Game 123: 3 blue; 1").is_err());
     }
 
     #[test]
     fn test_ids_of_possible_games() {
-        let games = parse_games("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-        let ids = ids_of_possible_games(&games, 12, 13, 14);
+        let games = parse_games(GAMES);
+        let bag = CubeSet::from_str(DEFAULT_BAG).unwrap();
+        let ids = ids_of_possible_games(&games, &bag);
         assert_eq!(vec![1,2,5], ids);
+
+        // without any green cubes in the bag
+        let bag = CubeSet::from_str("20 red, 20 blue").unwrap();
+        assert_eq!(Vec::<u32>::new(), ids_of_possible_games(&games, &bag));
+    }
+
+    #[test]
+    fn test_any_color() {
+        let games = parse_games(
+            "Game 1: 3 orange, 1 red; 2 purple
+            Game 2: 5 orange; 1 purple, 1 red
+            Game 3: 1 red",
+        );
+
+        let bag = CubeSet::from_str("4 orange, 2 purple, 1 red").unwrap();
+        assert_eq!(vec![1, 3], ids_of_possible_games(&games, &bag));
+
+        assert_eq!("5 orange, 2 purple, 1 red", min_bag(&games).to_string());
+        assert_eq!(
+            "3 orange, 2 purple, 1 red",
+            games[0].min_set_of_cubes().to_string()
+        );
+        assert_eq!("1 red", games[2].min_set_of_cubes().to_string());
+    }
+
+    #[test]
+    fn test_min_bag() {
+        let games = parse_games(GAMES);
+        let bag = min_bag(&games);
+        assert_eq!("15 blue, 13 green, 20 red", bag.to_string());
+        assert_eq!(vec![1, 2, 3, 4, 5], ids_of_possible_games(&games, &bag));
+
+        let powers: Vec<u32> = games
+            .iter()
+            .map(|game| game.min_set_of_cubes().power(&bag))
+            .collect();
+        assert_eq!(vec![48, 12, 1560, 630, 36], powers);
+    }
+
+    #[test]
+    fn test_power() {
+        let bag = CubeSet::from_str(DEFAULT_BAG).unwrap();
+
+        // never shows a green cube, so there are no green ones in the minimum set
+        let game = Game::from_str("Game 1: 3 blue, 4 red; 6 blue").unwrap();
+        assert_eq!(0, game.min_set_of_cubes().power(&bag));
+
+        // colours outside of the bag don't count
+        let game = Game::from_str("Game 2: 1 blue, 2 red, 3 green, 4 orange").unwrap();
+        assert_eq!(6, game.min_set_of_cubes().power(&bag));
+
+        let bag = CubeSet::from_str("5 orange, 5 red").unwrap();
+        assert_eq!(8, game.min_set_of_cubes().power(&bag));
     }
 }
\ No newline at end of file
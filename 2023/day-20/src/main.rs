@@ -1,12 +1,41 @@
-use std::{collections::HashMap, fs};
+use std::{
+    collections::{HashMap, VecDeque},
+    env, fs,
+};
 
 fn main() {
+    // cargo run -- [presses] [--reference]
+    let args: Vec<String> = env::args().collect();
+    let presses = args
+        .iter()
+        .skip(1)
+        .find(|a| !a.starts_with("--"))
+        .map(|n| n.parse::<usize>().expect("number of presses must be a number"))
+        .unwrap_or(1000);
     let input = fs::read_to_string("./src/input.txt").expect("could not read file");
-    let m = modules_from_str(&input);
-    let (low, high) = press_button_a_thousand_times(m);
+    let mut m = modules_from_str(&input);
+
+    // presses the button over the parsed modules one press at a time, slow but no extra memory
+    if args.iter().any(|a| a == "--reference") {
+        let (low, high) = press_button_n_times(&mut m, presses);
+        println!("{} {} = {}", low, high, low * high);
+        return;
+    }
+
+    let mut circuit = Circuit::compile(&m);
+    let ((low, high), period) = circuit.press_n_times(presses);
+
+    match period {
+        Some((start, period)) => println!("state repeats every {} presses after {}", period, start),
+        None => println!("no repeating state within {} presses", presses.min(HISTORY)),
+    }
+
     println!("{} {} = {}", low, high, low * high);
 }
 
+// how many states press_n_times remembers while looking for a cycle
+const HISTORY: usize = 100000;
+
 #[derive(Debug, Clone, PartialEq)]
 enum Module {
     FlipFlop(FlipFlop),
@@ -15,49 +44,32 @@ enum Module {
 }
 
 impl Module {
-    // i know this is horrible, i should get better with rust
-    fn targets(&self) -> Vec<String> {
+    fn targets(&self) -> &[String] {
         match self {
-            Module::FlipFlop(m) => m.targets.clone(),
-            Module::Conj(m) => m.targets.clone(),
-            Module::Broadcaster(b) => b.targets.clone(),
+            Module::FlipFlop(m) => &m.targets,
+            Module::Conj(m) => &m.targets,
+            Module::Broadcaster(b) => &b.targets,
         }
     }
-    fn value(&self) -> bool {
-        match self {
-            Module::FlipFlop(m) => m.value,
-            Module::Conj(m) => m.value,
-            Module::Broadcaster(b) => false,
-        }
-    }
-    fn accept(&self, source: String, signal: bool) -> (Module, bool) {
+
+    // updates the module state and returns the pulse it sends to all of its targets, if any
+    fn receive(&mut self, source: &str, signal: bool) -> Option<bool> {
         match self {
             Module::FlipFlop(m) => {
                 if signal {
-                    return (Module::FlipFlop(m.clone()), false);
+                    return None;
                 }
 
-                return (
-                    Module::FlipFlop(FlipFlop {
-                        value: !m.value,
-                        ..m.clone()
-                    }),
-                    true,
-                );
+                m.value = !m.value;
+                Some(m.value)
             }
             Module::Conj(c) => {
-                let mut new_c = c.clone();
-                new_c.inputs.insert(source, signal);
+                c.inputs.insert(source.to_string(), signal);
+                c.value = !c.inputs.values().all(|v| *v);
 
-                new_c.value = if new_c.inputs.iter().all(|kv| *kv.1 == true) {
-                    false
-                } else {
-                    true
-                };
-
-                (Module::Conj(new_c), true)
+                Some(c.value)
             }
-            any => (any.clone(), true),
+            Module::Broadcaster(_) => Some(signal),
         }
     }
 }
@@ -101,23 +113,23 @@ fn targets_from_str(s: &str) -> (String, Vec<String>) {
 
 fn module_from_str(s: &str) -> (String, Module) {
     let (name, targets) = targets_from_str(s);
-    match s.chars().nth(0).unwrap() {
+    match s.chars().next().unwrap() {
         '%' => (
             name,
             Module::FlipFlop(FlipFlop {
-                targets: targets,
+                targets,
                 value: false,
             }),
         ),
         '&' => (
             name,
             Module::Conj(Conj {
-                targets: targets,
+                targets,
                 value: false,
                 inputs: HashMap::new(),
             }),
         ),
-        _ => (name, Module::Broadcaster(Broadcaster { targets: targets })),
+        _ => (name, Module::Broadcaster(Broadcaster { targets })),
     }
 }
 
@@ -134,19 +146,16 @@ fn modules_from_str(s: &str) -> HashMap<String, Module> {
 
     for (key, module) in hm.iter() {
         for target_name in module.targets() {
-            if !updated_hm.contains_key(&target_name) {
+            if !updated_hm.contains_key(target_name) {
                 continue;
             }
 
             let target = updated_hm
-                .get_mut(&target_name)
-                .expect(&format!("key {} must exist", target_name));
+                .get_mut(target_name)
+                .unwrap_or_else(|| panic!("key {} must exist", target_name));
 
-            match target {
-                Module::Conj(c) => {
-                    c.inputs.insert(key.to_string(), false);
-                }
-                _ => {}
+            if let Module::Conj(c) = target {
+                c.inputs.insert(key.to_string(), false);
             }
         }
     }
@@ -154,69 +163,292 @@ fn modules_from_str(s: &str) -> HashMap<String, Module> {
     updated_hm
 }
 
-fn press_button_a_thousand_times(modules: HashMap<String, Module>) -> (usize, usize) {
-    let mut m = modules.clone();
+// reference implementation over the parsed modules, the compiled circuit must match it
+fn press_button_n_times(modules: &mut HashMap<String, Module>, n: usize) -> (usize, usize) {
     let mut low = 0;
     let mut high = 0;
 
-    for i in 0..1000 {
-        let (next_m, next_low, next_high) = propagate(
-            m,
-            "broadcaster".to_string(),
-            "broadcaster".to_string(),
-            false,
-        );
-        m = next_m;
-        low += next_low + 1;
-        high += next_high;
+    for _ in 0..n {
+        let (press_low, press_high) = press_button(modules);
+        low += press_low;
+        high += press_high;
     }
 
-    return (low, high);
+    (low, high)
 }
 
-fn propagate(
-    modules: HashMap<String, Module>,
-    source: String,
-    module_name: String,
-    signal: bool,
-) -> (HashMap<String, Module>, usize, usize) {
+// sends a low pulse to the broadcaster and processes pulses in the order they are sent,
+// returns the number of low and high pulses including the one from the button
+fn press_button(modules: &mut HashMap<String, Module>) -> (usize, usize) {
     let mut low = 0;
     let mut high = 0;
+    let mut queue: VecDeque<(String, String, bool)> = VecDeque::new();
+    queue.push_back(("button".to_string(), "broadcaster".to_string(), false));
+
+    while let Some((source, target, signal)) = queue.pop_front() {
+        if signal {
+            high += 1;
+        } else {
+            low += 1;
+        }
 
-    if !modules.contains_key(&module_name) {
-        return (modules, low, high);
+        // pulses to untyped modules like "output" are counted but go nowhere
+        let Some(module) = modules.get_mut(&target) else {
+            continue;
+        };
+
+        let Some(next_signal) = module.receive(&source, signal) else {
+            continue;
+        };
+
+        for next_target in module.targets() {
+            queue.push_back((target.clone(), next_target.clone(), next_signal));
+        }
     }
 
-    let m = modules.get(&module_name).unwrap();
-    let (updated, should_continue) = m.accept(source, signal);
-    let updated_value = updated.value();
-    let mut next_modules = modules.clone();
-    next_modules.insert(module_name.clone(), updated);
+    (low, high)
+}
+
+// all flip-flop values and conjunction memories packed into bits,
+// so a full circuit state can be hashed, compared and restored
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct State {
+    bits: Vec<u64>,
+}
+
+impl State {
+    fn new(len: usize) -> Self {
+        State {
+            bits: vec![0; len.div_ceil(64)],
+        }
+    }
 
-    if !should_continue {
-        return (next_modules, low, high);
+    fn get(&self, i: usize) -> bool {
+        self.bits[i / 64] & (1 << (i % 64)) != 0
     }
 
-    for target in m.targets().iter() {
-        if updated_value == true {
-            high += 1
+    fn set(&mut self, i: usize, value: bool) {
+        if value {
+            self.bits[i / 64] |= 1 << (i % 64);
         } else {
-            low += 1
+            self.bits[i / 64] &= !(1 << (i % 64));
         }
+    }
+}
 
-        let (propagated_modules, prop_low, prop_high) = propagate(
-            next_modules.clone(),
-            module_name.clone(),
-            target.clone(),
-            updated_value,
-        );
+#[derive(Debug, Clone, PartialEq)]
+enum Gate {
+    // bit of the flip-flop value
+    FlipFlop(usize),
+    // bit of the memory for each input module id
+    Conj(Vec<(usize, usize)>),
+    Broadcaster,
+    Output,
+}
+
+#[derive(Debug, Clone)]
+struct Circuit {
+    gates: Vec<Gate>,
+    targets: Vec<Vec<usize>>,
+    broadcaster: usize,
+    state: State,
+}
+
+impl Circuit {
+    fn compile(modules: &HashMap<String, Module>) -> Circuit {
+        let mut names: Vec<String> = modules.keys().cloned().collect();
+        for m in modules.values() {
+            names.extend(m.targets().iter().cloned());
+        }
+        names.sort();
+        names.dedup();
+
+        let ids: HashMap<&str, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+
+        let mut targets: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+        let mut inputs: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+        for (id, name) in names.iter().enumerate() {
+            let Some(m) = modules.get(name) else {
+                continue;
+            };
+            for target in m.targets() {
+                targets[id].push(ids[target.as_str()]);
+                inputs[ids[target.as_str()]].push(id);
+            }
+        }
+
+        // the current module state is carried over into the bits
+        let mut values: Vec<bool> = Vec::new();
+        let mut gates = Vec::with_capacity(names.len());
+        for (id, name) in names.iter().enumerate() {
+            let gate = match modules.get(name) {
+                Some(Module::FlipFlop(f)) => {
+                    values.push(f.value);
+                    Gate::FlipFlop(values.len() - 1)
+                }
+                Some(Module::Conj(c)) => {
+                    let mut memory = Vec::new();
+                    for input in inputs[id].iter() {
+                        memory.push((*input, values.len()));
+                        values.push(c.inputs.get(&names[*input]).copied().unwrap_or(false));
+                    }
+                    Gate::Conj(memory)
+                }
+                Some(Module::Broadcaster(_)) => Gate::Broadcaster,
+                None => Gate::Output,
+            };
+            gates.push(gate);
+        }
+
+        let mut state = State::new(values.len());
+        for (bit, value) in values.iter().enumerate() {
+            state.set(bit, *value);
+        }
+
+        Circuit {
+            broadcaster: ids["broadcaster"],
+            gates,
+            targets,
+            state,
+        }
+    }
+
+    fn snapshot(&self) -> State {
+        self.state.clone()
+    }
+
+    fn restore(&mut self, state: State) {
+        self.state = state;
+    }
+
+    // same as press_button, but over module ids and state bits
+    fn press(&mut self) -> (usize, usize) {
+        let mut low = 0;
+        let mut high = 0;
+        let mut queue: VecDeque<(usize, usize, bool)> = VecDeque::new();
+        queue.push_back((self.broadcaster, self.broadcaster, false));
+
+        while let Some((source, target, signal)) = queue.pop_front() {
+            if signal {
+                high += 1;
+            } else {
+                low += 1;
+            }
+
+            let next_signal = match &self.gates[target] {
+                Gate::FlipFlop(bit) => {
+                    if signal {
+                        continue;
+                    }
+                    let value = !self.state.get(*bit);
+                    self.state.set(*bit, value);
+                    value
+                }
+                Gate::Conj(memory) => {
+                    let mut all_high = true;
+                    for (input, bit) in memory.iter() {
+                        if *input == source {
+                            self.state.set(*bit, signal);
+                        }
+                        all_high = all_high && self.state.get(*bit);
+                    }
+                    !all_high
+                }
+                Gate::Broadcaster => signal,
+                Gate::Output => continue,
+            };
+
+            for next_target in self.targets[target].iter() {
+                queue.push_back((target, *next_target, next_signal));
+            }
+        }
+
+        (low, high)
+    }
+
+    // presses the button until a state repeats, returns the press count
+    // after which the cycle starts and its length. The circuit itself is not changed.
+    #[cfg(test)]
+    fn find_period(&self, max_presses: usize) -> Option<(usize, usize)> {
+        let mut circuit = self.clone();
+        let mut seen: HashMap<State, usize> = HashMap::from([(circuit.snapshot(), 0)]);
+
+        for i in 1..=max_presses {
+            circuit.press();
+
+            if let Some(start) = seen.insert(circuit.snapshot(), i) {
+                return Some((start, i - start));
+            }
+        }
 
-        low += prop_low;
-        high += prop_high;
-        next_modules.extend(propagated_modules);
+        None
     }
 
-    (next_modules, low, high)
+    // once the state repeats, the pulse counts of the remaining presses
+    // are taken from the recorded cycle instead of being simulated.
+    // Also returns the cycle, if there was one within the first HISTORY presses.
+    fn press_n_times(&mut self, n: usize) -> ((usize, usize), Option<(usize, usize)>) {
+        self.press_n_times_with_history(n, HISTORY)
+    }
+
+    // only the first max_history states are remembered, after that every press is simulated
+    fn press_n_times_with_history(
+        &mut self,
+        n: usize,
+        max_history: usize,
+    ) -> ((usize, usize), Option<(usize, usize)>) {
+        let mut states: Vec<State> = vec![self.snapshot()];
+        let mut counts: Vec<(usize, usize)> = vec![(0, 0)];
+        let mut seen: HashMap<State, usize> = HashMap::from([(self.snapshot(), 0)]);
+
+        for i in 1..=n.min(max_history) {
+            let (low, high) = self.press();
+            let (total_low, total_high) = counts[i - 1];
+            counts.push((total_low + low, total_high + high));
+
+            let state = self.snapshot();
+            let Some(start) = seen.get(&state).copied() else {
+                seen.insert(state.clone(), i);
+                states.push(state);
+                continue;
+            };
+
+            let period = i - start;
+            let cycles = (n - i) / period;
+            let rest = (n - i) % period;
+            let per_cycle = (counts[i].0 - counts[start].0, counts[i].1 - counts[start].1);
+            let partial = (
+                counts[start + rest].0 - counts[start].0,
+                counts[start + rest].1 - counts[start].1,
+            );
+
+            self.restore(states[start + rest].clone());
+
+            return (
+                (
+                    counts[i].0 + cycles * per_cycle.0 + partial.0,
+                    counts[i].1 + cycles * per_cycle.1 + partial.1,
+                ),
+                Some((start, period)),
+            );
+        }
+
+        let (mut total_low, mut total_high) = counts[counts.len() - 1];
+        // no cycle so far, the rest is simulated without keeping the history around
+        drop((states, counts, seen));
+
+        for _ in max_history..n {
+            let (low, high) = self.press();
+            total_low += low;
+            total_high += high;
+        }
+
+        ((total_low, total_high), None)
+    }
 }
 
 #[cfg(test)]
@@ -238,37 +470,66 @@ mod tests {
 
     #[test]
     fn test_press_n() {
-        let m = modules_from_str(
+        let mut m = modules_from_str(
             "broadcaster -> a, b, c
         %a -> b
         %b -> c
         %c -> inv
         &inv -> a",
         );
-        let (low, high) = press_button_a_thousand_times(m);
+        let (low, high) = press_button_n_times(&mut m, 1000);
         assert_eq!(4000, high);
         assert_eq!(8000, low);
     }
 
     #[test]
-    fn test_propagate() {
-        let m = modules_from_str(
+    fn test_press_n_with_output() {
+        let mut m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+        assert_eq!((4, 4), press_button(&mut m));
+        assert_eq!((4, 2), press_button(&mut m));
+        assert_eq!((5, 3), press_button(&mut m));
+        assert_eq!((4, 2), press_button(&mut m));
+
+        let mut m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+        assert_eq!((4250, 2750), press_button_n_times(&mut m, 1000));
+    }
+
+    #[test]
+    fn test_press_button() {
+        let mut m = modules_from_str(
             "broadcaster -> a, b, c
         %a -> b
         %b -> c
         %c -> inv
         &inv -> a",
         );
-        let (m2, low, high) = propagate(
-            m,
-            "broadcaster".to_string(),
-            "broadcaster".to_string(),
-            false,
-        );
-        assert_eq!(5, m2.len());
+        let (low, high) = press_button(&mut m);
+        assert_eq!(5, m.len());
         assert_eq!(4, high);
-        assert_eq!(7, low);
-        println!("{:?}", m2);
+        assert_eq!(8, low);
+
+        // every flip-flop ends up flipped twice
+        for name in ["a", "b", "c"] {
+            assert_eq!(
+                Module::FlipFlop(FlipFlop {
+                    targets: m[name].targets().to_vec(),
+                    value: false
+                }),
+                m[name]
+            );
+        }
     }
 
     #[test]
@@ -292,4 +553,135 @@ mod tests {
             format!("{:?}", inv)
         );
     }
+
+    #[test]
+    fn test_circuit_compile() {
+        let m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+        // ids follow the sorted names: a, b, broadcaster, con, inv, output
+        let c = Circuit::compile(&m);
+        assert_eq!(2, c.broadcaster);
+        assert_eq!(Gate::FlipFlop(0), c.gates[0]);
+        assert_eq!(Gate::FlipFlop(1), c.gates[1]);
+        assert_eq!(Gate::Conj(vec![(0, 2), (1, 3)]), c.gates[3]);
+        assert_eq!(Gate::Conj(vec![(0, 4)]), c.gates[4]);
+        assert_eq!(Gate::Output, c.gates[5]);
+        assert_eq!(State { bits: vec![0] }, c.state);
+    }
+
+    #[test]
+    fn test_circuit_matches_modules() {
+        let mut m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+        let mut c = Circuit::compile(&m);
+
+        for _ in 0..10 {
+            assert_eq!(press_button(&mut m), c.press());
+        }
+    }
+
+    #[test]
+    fn test_circuit_period() {
+        let m = modules_from_str(
+            "broadcaster -> a, b, c
+        %a -> b
+        %b -> c
+        %c -> inv
+        &inv -> a",
+        );
+        assert_eq!(Some((0, 1)), Circuit::compile(&m).find_period(10));
+
+        let m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+        let c = Circuit::compile(&m);
+        assert_eq!(Some((0, 4)), c.find_period(10));
+        assert_eq!(None, c.find_period(3));
+    }
+
+    #[test]
+    fn test_circuit_snapshot_restore() {
+        let m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+        let mut c = Circuit::compile(&m);
+        let initial = c.snapshot();
+
+        c.press();
+        let after_one = c.snapshot();
+        assert_ne!(initial, after_one);
+
+        assert_eq!((5, 3), {
+            c.press();
+            c.press()
+        });
+
+        c.restore(after_one);
+        c.press();
+        assert_eq!((5, 3), c.press());
+    }
+
+    #[test]
+    fn test_circuit_press_n_times() {
+        let m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+
+        for n in [0, 1, 3, 4, 5, 6, 7, 1000, 1001, 1002] {
+            let mut reference = m.clone();
+            let mut c = Circuit::compile(&m);
+            let (counts, period) = c.press_n_times(n);
+            assert_eq!(press_button_n_times(&mut reference, n), counts, "{}", n);
+            assert_eq!(if n >= 4 { Some((0, 4)) } else { None }, period, "{}", n);
+
+            // the fast-forwarded state is the same as after simulating every press
+            assert_eq!(Circuit::compile(&reference).snapshot(), c.snapshot(), "{}", n);
+        }
+    }
+
+    #[test]
+    fn test_circuit_press_n_times_without_history() {
+        let m = modules_from_str(
+            "broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output",
+        );
+
+        // the cycle is 4 presses long, too long to be seen in 3 remembered states
+        for n in [0, 2, 3, 4, 10, 1001] {
+            let mut reference = m.clone();
+            let mut c = Circuit::compile(&m);
+            assert_eq!(
+                (press_button_n_times(&mut reference, n), None),
+                c.press_n_times_with_history(n, 3),
+                "{}",
+                n
+            );
+            assert_eq!(Circuit::compile(&reference).snapshot(), c.snapshot(), "{}", n);
+        }
+    }
 }
@@ -1,10 +1,13 @@
-use core::panic;
-use std::{collections::HashSet, fs};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+};
 
 fn main() {
     let input = fs::read_to_string("./src/input.txt").expect("could not read file");
     let game = game_from_str(&input);
     println!("{}", game.walk(64));
+    println!("{}", game.walk_infinite_extrapolated(26501365));
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -20,27 +23,103 @@ struct Game {
 
 impl Game {
     fn walk(&self, steps: usize) -> usize {
-        let mut prev_visited: HashSet<(usize, usize)> = HashSet::new();
-        prev_visited.insert(self.start);
-        let mut next_visited: HashSet<(usize, usize)> = HashSet::new();
-
-        for i in 0..steps {
-            next_visited.clear();
-            next_visited.insert(self.start);
-
-            for tile in prev_visited.clone().iter() {
-                for successor in self.get_successors(*tile).iter() {
-                    if !prev_visited.contains(successor) {
-                        next_visited.insert(*successor);
-                    }
+        self.walk_many(&[steps])[0]
+    }
+
+    // number of plots reachable in exactly k steps for every k, all from the same distance map
+    fn walk_many(&self, steps: &[usize]) -> Vec<usize> {
+        let distances = self.distances();
+
+        steps
+            .iter()
+            .map(|k| {
+                distances
+                    .iter()
+                    .flatten()
+                    .flatten()
+                    .filter(|d| **d <= *k && *d % 2 == k % 2)
+                    .count()
+            })
+            .collect()
+    }
+
+    // shortest number of steps from S to every plot, None for rocks and unreachable plots
+    fn distances(&self) -> Vec<Vec<Option<usize>>> {
+        let mut result = vec![vec![None; self.tiles[0].len()]; self.tiles.len()];
+        result[self.start.0][self.start.1] = Some(0);
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::from([self.start]);
+
+        while let Some(pos) = queue.pop_front() {
+            let distance = result[pos.0][pos.1].unwrap_or(0);
+
+            for next in self.get_successors(pos) {
+                if result[next.0][next.1].is_none() {
+                    result[next.0][next.1] = Some(distance + 1);
+                    queue.push_back(next);
                 }
             }
+        }
+
+        result
+    }
+
+    // the map repeats in every direction, so positions are taken modulo its size.
+    // A plot reached in d steps can be reached again in d + 2, d + 4... steps by going
+    // back and forth, so it counts if d <= steps and d has the same parity as steps.
+    fn walk_infinite(&self, steps: usize) -> usize {
+        let rows = self.tiles.len() as i64;
+        let cols = self.tiles[0].len() as i64;
+        let start = (self.start.0 as i64, self.start.1 as i64);
+
+        let mut distances: HashMap<(i64, i64), usize> = HashMap::from([(start, 0)]);
+        let mut queue: VecDeque<(i64, i64)> = VecDeque::from([start]);
+
+        while let Some(pos) = queue.pop_front() {
+            let distance = distances[&pos];
+            if distance == steps {
+                continue;
+            }
 
-            prev_visited = next_visited.clone();
+            for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let next = (pos.0 + dr, pos.1 + dc);
+                let tile = self.tiles[next.0.rem_euclid(rows) as usize][next.1.rem_euclid(cols) as usize];
+
+                if tile == Tile::Garden && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
         }
-        
-        // println!("{:?}", next_visited);
-        next_visited.len()
+
+        distances.values().filter(|d| *d % 2 == steps % 2).count()
+    }
+
+    // With the start row and column free of rocks the reachable area grows by whole maps,
+    // so the counts at n, n + size, n + 2 * size steps (n = steps % size) lie on
+    // a quadratic in the number of maps walked, which is then evaluated at steps / size.
+    fn walk_infinite_extrapolated(&self, steps: usize) -> usize {
+        let rows = self.tiles.len();
+        let cols = self.tiles.first().map_or(0, |row| row.len());
+        assert_eq!(rows, cols, "the map has to be square to repeat with one period");
+
+        let size = rows;
+        let n = steps % size;
+        let x = steps / size;
+
+        if x < 3 {
+            return self.walk_infinite(steps);
+        }
+
+        let a0 = self.walk_infinite(n) as i64;
+        let a1 = self.walk_infinite(n + size) as i64;
+        let a2 = self.walk_infinite(n + 2 * size) as i64;
+
+        // Newton's forward differences: f(x) = a0 + x * d1 + x * (x - 1) / 2 * d2
+        let d1 = a1 - a0;
+        let d2 = a2 + a0 - 2 * a1;
+        let x = x as i64;
+
+        (a0 + x * d1 + x * (x - 1) / 2 * d2) as usize
     }
 
     fn get_successors(&self, pos: (usize, usize)) -> Vec<(usize, usize)> {
@@ -54,15 +133,15 @@ impl Game {
             result.push((pos.0, pos.1-1));
         }
 
-        if pos.0 < self.tiles.len() {
+        if pos.0 + 1 < self.tiles.len() {
             result.push((pos.0+1, pos.1));
         }
 
-        if pos.1 < self.tiles[0].len() {
+        if pos.1 + 1 < self.tiles[pos.0].len() {
             result.push((pos.0, pos.1+1));
         }
 
-        return result.into_iter().filter(|pos| matches!(self.tiles[pos.0][pos.1], Tile::Garden)).collect();
+        result.into_iter().filter(|pos| matches!(self.tiles[pos.0][pos.1], Tile::Garden)).collect()
     }
 }
 
@@ -90,7 +169,7 @@ fn game_from_str(s: &str) -> Game {
         game.tiles.push(row);
     }
 
-    return game
+    game
 }
 
 #[cfg(test)]
@@ -112,4 +191,85 @@ mod tests {
         ...........");
     assert_eq!(16, game.walk(6));
     }
+
+    #[test]
+    fn test_walk_many() {
+        let game = game_from_str(EXAMPLE);
+        assert_eq!(vec![1, 2, 4, 6, 16, 16], game.walk_many(&[0, 1, 2, 3, 6, 6]));
+        assert_eq!(game.walk(6), game.walk_infinite(6));
+    }
+
+    #[test]
+    fn test_distances() {
+        let game = game_from_str(
+            "..#
+            .#.
+            ..S",
+        );
+        let d = game.distances();
+        assert_eq!(Some(0), d[2][2]);
+        assert_eq!(Some(4), d[0][0]);
+        assert_eq!(None, d[0][2]);
+        assert_eq!(None, d[1][1]);
+        assert_eq!(vec![1, 2, 2, 3, 3], game.walk_many(&[0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_walk_infinite() {
+        let game = game_from_str(EXAMPLE);
+        assert_eq!(16, game.walk_infinite(6));
+        assert_eq!(50, game.walk_infinite(10));
+        assert_eq!(1594, game.walk_infinite(50));
+        assert_eq!(6536, game.walk_infinite(100));
+    }
+
+    #[test]
+    fn test_walk_infinite_extrapolated() {
+        // like the real input: free start row, start column and border, no detours around rocks
+        let game = game_from_str(
+            "...........
+            .#.......#.
+            ...#...#...
+            ...........
+            .#.......#.
+            .....S.....
+            .#.......#.
+            ...........
+            ...#...#...
+            .#.......#.
+            ...........",
+        );
+
+        for steps in [5, 16, 27, 38, 40, 49, 63, 82, 93, 100] {
+            assert_eq!(
+                game.walk_infinite(steps),
+                game.walk_infinite_extrapolated(steps),
+                "{}",
+                steps
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the map has to be square")]
+    fn test_walk_infinite_extrapolated_not_square() {
+        let game = game_from_str(
+            ".....
+            ..S..
+            .....",
+        );
+        game.walk_infinite_extrapolated(20);
+    }
+
+    const EXAMPLE: &str = "...........
+        .....###.#.
+        .###.##..#.
+        ..#.#...#..
+        ....#.#....
+        .##..S####.
+        .##..#...#.
+        .......##..
+        .##.#.####.
+        .##..##.##.
+        ...........";
 }
\ No newline at end of file
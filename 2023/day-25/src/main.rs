@@ -0,0 +1,210 @@
+use std::{
+    collections::{BinaryHeap, HashMap},
+    env, fs,
+};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let path = args
+        .get(1)
+        .expect("path is required: cargo run -- src/input.txt");
+    let input = fs::read_to_string(path).expect("could not read file");
+
+    let graph = graph_from_str(&input);
+    let cut = graph.min_cut();
+
+    for (a, b) in cut.edges.iter() {
+        println!("cut {}/{}", graph.names[*a], graph.names[*b]);
+    }
+
+    let (left, right) = cut.sizes(graph.names.len());
+    println!("{} * {} = {}", left, right, left * right);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Graph {
+    names: Vec<String>,
+    edges: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cut {
+    // component ids on one side of the cut, the rest of the graph is on the other side
+    side: Vec<usize>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Cut {
+    fn sizes(&self, total: usize) -> (usize, usize) {
+        (self.side.len(), total - self.side.len())
+    }
+}
+
+fn graph_from_str(s: &str) -> Graph {
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    let mut names = Vec::new();
+    let mut edges = Vec::new();
+
+    let mut id_of = |name: &str, names: &mut Vec<String>| -> usize {
+        *ids.entry(name.to_string()).or_insert_with(|| {
+            names.push(name.to_string());
+            names.len() - 1
+        })
+    };
+
+    for line in s.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let (from, to) = line
+            .split_once(':')
+            .unwrap_or_else(|| panic!("bad line: {}", line));
+        let from = id_of(from.trim(), &mut names);
+
+        for target in to.split_whitespace() {
+            let target = id_of(target, &mut names);
+            edges.push((from, target));
+        }
+    }
+
+    Graph { names, edges }
+}
+
+impl Graph {
+    // Stoer-Wagner: every phase orders the merged nodes by how strongly they are connected
+    // to the already picked ones, the last picked node gives a candidate cut,
+    // then the last two nodes are merged. The best candidate is the global minimum cut.
+    fn min_cut(&self) -> Cut {
+        let n = self.names.len();
+        let mut adj: Vec<HashMap<usize, usize>> = vec![HashMap::new(); n];
+        for (a, b) in self.edges.iter() {
+            if a == b {
+                continue;
+            }
+            *adj[*a].entry(*b).or_insert(0) += 1;
+            *adj[*b].entry(*a).or_insert(0) += 1;
+        }
+
+        let mut groups: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+        let mut active: Vec<usize> = (0..n).collect();
+        let mut best: Option<(usize, Vec<usize>)> = None;
+
+        while active.len() > 1 {
+            let (s, t, weight) = Self::phase(&adj, &active, n);
+
+            if best.as_ref().is_none_or(|(w, _)| weight < *w) {
+                best = Some((weight, groups[t].clone()));
+            }
+
+            // merge t into s
+            let t_adj = std::mem::take(&mut adj[t]);
+            for (k, w) in t_adj {
+                adj[k].remove(&t);
+                if k == s {
+                    continue;
+                }
+                *adj[s].entry(k).or_insert(0) += w;
+                *adj[k].entry(s).or_insert(0) += w;
+            }
+            let t_group = std::mem::take(&mut groups[t]);
+            groups[s].extend(t_group);
+            active.retain(|v| *v != t);
+        }
+
+        let mut side = best.map(|(_, side)| side).unwrap_or_default();
+        side.sort();
+
+        let mut in_side = vec![false; n];
+        for v in side.iter() {
+            in_side[*v] = true;
+        }
+        let edges = self
+            .edges
+            .iter()
+            .filter(|(a, b)| in_side[*a] != in_side[*b])
+            .cloned()
+            .collect();
+
+        Cut { side, edges }
+    }
+
+    // returns the last two added nodes and the weight of the cut between the last one and the rest
+    fn phase(adj: &[HashMap<usize, usize>], active: &[usize], n: usize) -> (usize, usize, usize) {
+        let mut added = vec![false; n];
+        let mut weights = vec![0; n];
+        let mut heap: BinaryHeap<(usize, usize)> = BinaryHeap::new();
+        let mut order: Vec<usize> = Vec::with_capacity(active.len());
+
+        for v in active.iter() {
+            heap.push((0, *v));
+        }
+
+        while order.len() < active.len() {
+            let (w, v) = heap.pop().expect("every active node is in the heap");
+            if added[v] || w != weights[v] {
+                continue;
+            }
+
+            added[v] = true;
+            order.push(v);
+
+            for (k, kw) in adj[v].iter() {
+                if !added[*k] {
+                    weights[*k] += kw;
+                    heap.push((weights[*k], *k));
+                }
+            }
+        }
+
+        let t = order[order.len() - 1];
+        let s = order[order.len() - 2];
+
+        (s, t, weights[t])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "jqt: rhn xhk nvd
+    rsh: frs pzl lsr
+    xhk: hfx
+    cmg: qnr nvd lhk bvb
+    rhn: xhk bvb hfx
+    bvb: xhk hfx
+    pzl: lsr hfx nvd
+    qnr: nvd
+    ntq: jqt hfx bvb xhk
+    nvd: lhk
+    lsr: lhk
+    rzs: qnr cmg lsr rsh
+    frs: qnr lhk lsr";
+
+    #[test]
+    fn test_graph_from_str() {
+        let g = graph_from_str(EXAMPLE);
+        assert_eq!(15, g.names.len());
+        assert_eq!(33, g.edges.len());
+        assert_eq!("jqt", g.names[0]);
+        assert_eq!((0, 1), g.edges[0]);
+    }
+
+    #[test]
+    fn test_min_cut() {
+        let g = graph_from_str(EXAMPLE);
+        let cut = g.min_cut();
+
+        let (left, right) = cut.sizes(g.names.len());
+        assert_eq!(54, left * right);
+
+        let mut cut_edges: Vec<String> = cut
+            .edges
+            .iter()
+            .map(|(a, b)| {
+                let mut pair = [g.names[*a].as_str(), g.names[*b].as_str()];
+                pair.sort();
+                pair.join("/")
+            })
+            .collect();
+        cut_edges.sort();
+        assert_eq!(vec!["bvb/cmg", "hfx/pzl", "jqt/nvd"], cut_edges);
+    }
+}
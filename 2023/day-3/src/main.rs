@@ -1,9 +1,11 @@
-use core::num;
-use std::{io::Empty, fs, env};
+use std::{env, fmt, fs};
 
 use regex::Regex;
 
 fn main() {
+    // cargo run -- src/input.txt [--numbers SYMBOLS]
+    //     [--symbols SYMBOLS [--count N] [--aggregate sum|product]]
+    // SYMBOLS is a list of symbol characters like "*#", or "any"
     let args: Vec<String> = env::args().collect();
     let path = args
         .get(1)
@@ -13,22 +15,127 @@ fn main() {
     
     let matrix = matrix_from_str(&input);
 
-    println!("Part numbers: {}", get_part_numbers(&matrix).iter().sum::<i32>());
-    println!("Gear ratios: {}", get_gear_ratios(&matrix).iter().sum::<i32>());
+    let option = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+    };
+
+    if option("--numbers").is_none() && option("--symbols").is_none() {
+        println!("Part numbers: {}", get_part_numbers(&matrix).iter().sum::<i32>());
+        println!("Gear ratios: {}", get_gear_ratios(&matrix).iter().sum::<i64>());
+        return;
+    }
+
+    if let Some(symbols) = option("--numbers") {
+        let symbols = Symbols::from_arg(symbols);
+        let values: Vec<i32> = matrix
+            .numbers_adjacent_to(&symbols)
+            .iter()
+            .map(|id| matrix.number(*id).value)
+            .collect();
+        println!(
+            "{} numbers next to {}, sum {}",
+            values.len(),
+            symbols,
+            values.iter().sum::<i32>()
+        );
+    }
+
+    if let Some(symbols) = option("--symbols") {
+        let symbols = Symbols::from_arg(symbols);
+        let count = option("--count").map(|c| c.parse::<usize>().expect("count is a number"));
+        let aggregate = match option("--aggregate").map(|a| a.as_str()) {
+            None | Some("product") => Aggregate::Product,
+            Some("sum") => Aggregate::Sum,
+            Some(other) => {
+                println!("unknown aggregate: {}, use sum or product", other);
+                return;
+            }
+        };
+
+        let mut total = 0;
+        for found in matrix.symbols_with_numbers(&symbols, count) {
+            let value = matrix.aggregate(&found, aggregate);
+            println!("{} at {},{}: {}", found.symbol, found.row, found.col, value);
+            total += value;
+        }
+        println!("Total: {}", total);
+    }
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(Copy)]
+#[derive(PartialEq)]
 enum Item {
-    PartNumber(i32),
+    // id of the number covering the cell
+    Number(usize),
     Symbol(char),
     Empty
 }
 
+// a number written on one row, from col_start up to but not including col_end
+#[derive(Debug, Clone, PartialEq)]
+struct Number {
+    id: usize,
+    value: i32,
+    row: usize,
+    col_start: usize,
+    col_end: usize,
+}
+
+// the symbols a query is about
+#[derive(Debug, Clone, PartialEq)]
+enum Symbols {
+    Any,
+    Only(Vec<char>),
+}
+
+impl Symbols {
+    fn from_arg(s: &str) -> Symbols {
+        match s {
+            "any" => Symbols::Any,
+            _ => Symbols::Only(s.chars().collect()),
+        }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Symbols::Any => true,
+            Symbols::Only(symbols) => symbols.contains(&c),
+        }
+    }
+}
+
+impl fmt::Display for Symbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Symbols::Any => write!(f, "any symbol"),
+            Symbols::Only(symbols) => write!(f, "{}", symbols.iter().collect::<String>()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aggregate {
+    Sum,
+    Product,
+}
+
+// a symbol and the ids of the numbers around it
+#[derive(Debug, Clone, PartialEq)]
+struct SymbolNumbers {
+    symbol: char,
+    row: usize,
+    col: usize,
+    numbers: Vec<usize>,
+}
+
 #[derive(Debug)]
 struct Matrix {
-    items: Vec<Vec<Item>>
+    items: Vec<Vec<Item>>,
+    numbers: Vec<Number>,
 }
 
 impl Matrix {
@@ -36,32 +143,99 @@ impl Matrix {
         *self.items.get(row).unwrap().get(col).unwrap()
     }
 
-    fn has_symbol_neighbors(&self, row: usize, col: usize) -> bool {
-        self.get_neighbors(row, col).iter().any(|item| matches!(item, Item::Symbol(..)))
+    fn number(&self, id: usize) -> &Number {
+        &self.numbers[id]
+    }
+
+    // every cell around the number, clipped to the matrix
+    fn number_border(&self, number: &Number) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        let rows = number.row.saturating_sub(1)..(number.row + 2).min(self.items.len());
+
+        for row in rows {
+            let cols =
+                number.col_start.saturating_sub(1)..(number.col_end + 1).min(self.items[row].len());
+            for col in cols {
+                if row != number.row || col < number.col_start || col >= number.col_end {
+                    cells.push((row, col));
+                }
+            }
+        }
+
+        cells
+    }
+
+    fn has_symbol_neighbors(&self, number: &Number, symbols: &Symbols) -> bool {
+        self.number_border(number)
+            .iter()
+            .any(|(row, col)| match self.get(*row, *col) {
+                Item::Symbol(c) => symbols.contains(c),
+                _ => false,
+            })
     }
 
-    fn gear_ratio(&self, row: usize, col: usize) -> Option<i32> {
-        match self.get(row, col) {
-            Item::Symbol('*') => {
-                let mut first_part_number = -1;
+    // ids of the numbers next to at least one of the symbols
+    fn numbers_adjacent_to(&self, symbols: &Symbols) -> Vec<usize> {
+        self.numbers
+            .iter()
+            .filter(|number| self.has_symbol_neighbors(number, symbols))
+            .map(|number| number.id)
+            .collect()
+    }
 
-                for neighbor in self.get_neighbors(row, col).iter() {
-                    if let Item::PartNumber(n) = neighbor {
-                        if first_part_number == -1 {
-                            first_part_number = *n;
-                        } else if *n == first_part_number {
-                            continue;
-                        } else {
-                            return Some(*n * first_part_number);
-                        }
-                    }
+    // every one of the symbols, row by row, with exactly count numbers around it
+    // or with any number of them when there's no count
+    fn symbols_with_numbers(&self, symbols: &Symbols, count: Option<usize>) -> Vec<SymbolNumbers> {
+        let mut found = Vec::new();
+
+        for (row, items) in self.items.iter().enumerate() {
+            for (col, item) in items.iter().enumerate() {
+                let Item::Symbol(symbol) = item else {
+                    continue;
+                };
+                if !symbols.contains(*symbol) {
+                    continue;
                 }
-                
-                return None;
-            },
-            _ => None
+
+                let numbers = self.neighbor_numbers(row, col);
+                if count.is_none_or(|count| numbers.len() == count) {
+                    found.push(SymbolNumbers {
+                        symbol: *symbol,
+                        row,
+                        col,
+                        numbers,
+                    });
+                }
+            }
         }
 
+        found
+    }
+
+    // a symbol without numbers sums up to 0, and its product is 1
+    fn aggregate(&self, found: &SymbolNumbers, aggregate: Aggregate) -> i64 {
+        let values = found.numbers.iter().map(|id| self.number(*id).value as i64);
+        match aggregate {
+            Aggregate::Sum => values.sum(),
+            Aggregate::Product => values.product(),
+        }
+    }
+
+    // ids of the different numbers around the cell, a number touching it with
+    // several of its digits is only there once
+    fn neighbor_numbers(&self, row: usize, col: usize) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .get_neighbors(row, col)
+            .iter()
+            .filter_map(|item| match item {
+                Item::Number(id) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        ids.sort();
+        ids.dedup();
+
+        ids
     }
 
     fn get_neighbors(&self, row: usize, col: usize) -> Vec<Item> {
@@ -75,7 +249,7 @@ impl Matrix {
                 items.push(self.get(row-1, col-1));    
             }
             // top right
-            if col < self.items.get(0).unwrap().len() - 1 {
+            if col < self.items.first().unwrap().len() - 1 {
                 items.push(self.get(row-1, col+1));    
             }
         }
@@ -88,7 +262,7 @@ impl Matrix {
                 items.push(self.get(row+1, col-1));
             }
             // bottom right
-            if col < self.items.get(0).unwrap().len() - 1 {
+            if col < self.items.first().unwrap().len() - 1 {
                 items.push(self.get(row+1, col+1));
             }
         }
@@ -98,55 +272,34 @@ impl Matrix {
             items.push(self.get(row, col-1));
         }
 
-        if col < self.items.get(0).unwrap().len() - 1 {
+        if col < self.items.first().unwrap().len() - 1 {
             // right
             items.push(self.get(row, col+1));
         }
 
-        return items;
+        items
     }
 }
 
-fn get_gear_ratios(m: &Matrix) -> Vec<i32> {
-    let mut ratios = Vec::new();
-
-    for (row_index, row) in m.items.iter().enumerate() {
-        for (col_index, item) in row.iter().enumerate() {
-            if let Some(gear_ratio) = m.gear_ratio(row_index, col_index) {
-                ratios.push(gear_ratio);
-            }
-        }
-    }
-
-    return ratios;
+// a gear is a * with exactly two numbers around it
+fn get_gear_ratios(m: &Matrix) -> Vec<i64> {
+    m.symbols_with_numbers(&Symbols::Only(vec!['*']), Some(2))
+        .iter()
+        .map(|gear| m.aggregate(gear, Aggregate::Product))
+        .collect()
 }
 
 fn get_part_numbers(m: &Matrix) -> Vec<i32> {
-    let mut numbers: Vec<i32> = Vec::new();
-    for (row_index, row) in m.items.iter().enumerate() {
-        let mut number_added: bool = false;
-
-        for (col_index, item) in row.iter().enumerate() {
-            match item {
-                Item::PartNumber(n) => {
-                    if m.has_symbol_neighbors(row_index, col_index) && !number_added {
-                        number_added = true;
-                        numbers.push(*n);
-                    }
-                },
-                _ => {
-                    number_added = false;
-                }
-            }
-        }
-    }
-
-    return numbers;
+    m.numbers_adjacent_to(&Symbols::Any)
+        .iter()
+        .map(|id| m.number(*id).value)
+        .collect()
 }
 
 fn matrix_from_str(s: &str) -> Matrix {
     let mut engine = Matrix{
-        items: Vec::new()
+        items: Vec::new(),
+        numbers: Vec::new()
     };
     let line_regexp = Regex::new(
         r"(?m)((?P<empty>\.)|(?P<number>\d+)|(?P<symbol>[^\d\.]))"
@@ -154,55 +307,167 @@ fn matrix_from_str(s: &str) -> Matrix {
 
 
     for (row, line) in s.lines().enumerate() {
-        let mut col = 0;
-
-        let v:Vec<Item> = line_regexp.captures_iter(line.trim()).flat_map(|m| {
-            col += 1;
+        let mut v: Vec<Item> = Vec::new();
 
+        for m in line_regexp.captures_iter(line.trim()) {
             if m.name("empty").is_some() {
-                return vec![Item::Empty]
+                v.push(Item::Empty);
             } else if let Some(symbol) = m.name("symbol") {
-                return vec![Item::Symbol(symbol.as_str().chars().next().unwrap())];
+                v.push(Item::Symbol(symbol.as_str().chars().next().unwrap()));
             } else {
-                let part_number_str = m.name("number").unwrap().as_str();
-                let part_number_int = part_number_str.parse().unwrap();
-
-                return vec![Item::PartNumber(part_number_int); part_number_str.len()];
+                let number = m.name("number").unwrap();
+                let id = engine.numbers.len();
+
+                engine.numbers.push(Number {
+                    id,
+                    value: number.as_str().parse().unwrap(),
+                    row,
+                    col_start: v.len(),
+                    col_end: v.len() + number.len(),
+                });
+                v.extend(vec![Item::Number(id); number.len()]);
             }
-        }).collect();
+        }
 
         engine.items.push(v);
     }
 
-    return engine;
+    engine
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const SCHEMATIC: &str = "467..114..
+        ...*......
+        ..35..633.
+        ......#...
+        617*......
+        .....+.58.
+        ..592.....
+        ......755.
+        ...$.*....
+        .664.598..";
+
     #[test]
     fn test_matrix_from_str() {
         let m = matrix_from_str(".....+.58.");
         assert_eq!(
-            "Matrix { items: [[Empty, Empty, Empty, Empty, Empty, Symbol('+'), Empty, PartNumber(58), PartNumber(58), Empty]] }",
-            format!("{:?}", m)
+            "[[Empty, Empty, Empty, Empty, Empty, Symbol('+'), Empty, Number(0), Number(0), Empty]]",
+            format!("{:?}", m.items)
+        );
+        assert_eq!(
+            vec![Number {
+                id: 0,
+                value: 58,
+                row: 0,
+                col_start: 7,
+                col_end: 9
+            }],
+            m.numbers
         );
     }
 
     #[test]
     fn test_get_part_numbers() {
-        let m = matrix_from_str("467..114..
-        ...*......
-        ..35..633.
-        ......#...
-        617*......
-        .....+.58.
-        ..592.....
-        ......755.
-        ...$.*....
-        .664.598..");
+        let m = matrix_from_str(SCHEMATIC);
         assert_eq!(vec![467i32, 35i32, 633i32, 617i32, 592i32, 755i32, 664i32, 598i32], get_part_numbers(&m));
-        assert_eq!(vec![16345i32, 451490i32], get_gear_ratios(&m));
+        assert_eq!(vec![16345i64, 451490i64], get_gear_ratios(&m));
+    }
+
+    #[test]
+    fn test_neighbor_numbers() {
+        let m = matrix_from_str(SCHEMATIC);
+        // 467 touches the gear with one digit, 35 with two
+        assert_eq!(vec![0, 2], m.neighbor_numbers(1, 3));
+        assert_eq!(vec![4], m.neighbor_numbers(4, 3));
+        assert_eq!(
+            vec![(0, 3), (1, 0), (1, 1), (1, 2), (1, 3)],
+            m.number_border(m.number(0))
+        );
+    }
+
+    #[test]
+    fn test_equal_numbers() {
+        // the same number on both sides of a gear is still two numbers
+        let m = matrix_from_str(
+            "12*12
+            .....
+            7*7..",
+        );
+        assert_eq!(vec![12, 12, 7, 7], get_part_numbers(&m));
+        assert_eq!(vec![144, 49], get_gear_ratios(&m));
+
+        // a third number means it's not a gear
+        let m = matrix_from_str(
+            "12*12
+            ..3..",
+        );
+        assert_eq!(Vec::<i64>::new(), get_gear_ratios(&m));
+    }
+
+    #[test]
+    fn test_queries() {
+        let m = matrix_from_str(SCHEMATIC);
+        let values =
+            |ids: Vec<usize>| -> Vec<i32> { ids.iter().map(|id| m.number(*id).value).collect() };
+
+        assert_eq!(
+            vec![633],
+            values(m.numbers_adjacent_to(&Symbols::from_arg("#")))
+        );
+        assert_eq!(
+            vec![592, 664],
+            values(m.numbers_adjacent_to(&Symbols::from_arg("+$")))
+        );
+        assert_eq!(
+            vec![467, 35, 617, 755, 598],
+            values(m.numbers_adjacent_to(&Symbols::from_arg("*")))
+        );
+        assert_eq!(
+            Vec::<i32>::new(),
+            values(m.numbers_adjacent_to(&Symbols::from_arg("@")))
+        );
+
+        // the * next to 617 only has one number
+        let lonely = m.symbols_with_numbers(&Symbols::from_arg("*"), Some(1));
+        assert_eq!(
+            vec![SymbolNumbers {
+                symbol: '*',
+                row: 4,
+                col: 3,
+                numbers: vec![4]
+            }],
+            lonely
+        );
+        assert_eq!(617, m.aggregate(&lonely[0], Aggregate::Product));
+
+        let all = m.symbols_with_numbers(&Symbols::Any, None);
+        assert_eq!(6, all.len());
+        let sums: Vec<i64> = all.iter().map(|s| m.aggregate(s, Aggregate::Sum)).collect();
+        assert_eq!(vec![502, 633, 617, 592, 664, 1353], sums);
+
+        // no number here touches more than one symbol, so the sums add up to the part numbers
+        assert_eq!(
+            get_part_numbers(&m).iter().map(|v| *v as i64).sum::<i64>(),
+            sums.iter().sum::<i64>()
+        );
+
+        // a number touching two symbols is counted once for each of them
+        let m = matrix_from_str(
+            "*12#
+            ..3.",
+        );
+        assert_eq!(vec![12, 3], get_part_numbers(&m));
+        let sums: Vec<i64> = m
+            .symbols_with_numbers(&Symbols::Any, None)
+            .iter()
+            .map(|s| m.aggregate(s, Aggregate::Sum))
+            .collect();
+        assert_eq!(vec![12, 15], sums);
+
+        assert_eq!("any symbol", Symbols::from_arg("any").to_string());
+        assert_eq!("*#", Symbols::from_arg("*#").to_string());
     }
 }
\ No newline at end of file
@@ -1,67 +1,175 @@
-use std::{collections::{HashSet, VecDeque, HashMap, hash_map::Entry}, env, fs};
+use std::{
+    env,
+    fmt::{self, Display},
+    fs::File,
+    io::{BufRead, BufReader},
+};
 
 fn main() {
+    // cargo run -- src/input.txt [--trace]
     let args: Vec<String> = env::args().collect();
     let path = args
         .get(1)
         .expect("path is required: cargo run -- src/input.txt");
-    let input = fs::read_to_string(path).expect("could not read file");
+    let file = File::open(path).expect("could not read file");
 
-    let points_part1: u32 = input
-        .lines()
-        .map(parse_card)
-        .map(|card| card.get_points())
-        .sum();
+    let mut cascade = if args.iter().any(|a| a == "--trace") {
+        Cascade::with_trace()
+    } else {
+        Cascade::new()
+    };
+    let mut points_part1: u32 = 0;
+
+    for line in BufReader::new(file).lines() {
+        let card = parse_card(&line.expect("could not read line"));
+        points_part1 += card.get_points();
+        cascade.push(&card);
+    }
+
+    if let Some(trace) = cascade.trace() {
+        for card in trace {
+            println!("{}", card);
+        }
+    }
 
     println!("Total points: {}", points_part1);
 
-    println!("Card count: {}", count_cards(&input));
+    println!("Card count: {}", cascade.total());
 }
 
-#[derive(Debug)]
-#[derive(Clone)]
+// the most matches a card can have, numbers are below 128
+const PENDING: usize = 128;
+
+#[derive(Debug, Clone)]
 struct Card {
     winning_numbers: Vec<u32>,
     my_numbers: Vec<u32>,
 }
 
+fn number_set(numbers: &[u32]) -> u128 {
+    numbers.iter().fold(0, |set, number| {
+        assert!(*number < 128, "number {} doesn't fit in the set", number);
+        set | 1 << number
+    })
+}
+
 impl Card {
+    #[cfg(test)]
     fn get_matches(&self) -> Vec<u32> {
+        let mine = number_set(&self.my_numbers);
         self.winning_numbers
             .iter()
-            .collect::<HashSet<_>>()
-            .intersection(&self.my_numbers.iter().collect::<HashSet<_>>())
-            .map(|number| **number)
+            .filter(|number| mine & 1 << *number != 0)
+            .copied()
             .collect()
     }
 
+    fn match_count(&self) -> usize {
+        (number_set(&self.winning_numbers) & number_set(&self.my_numbers)).count_ones() as usize
+    }
+
     fn get_points(&self) -> u32 {
-        self.get_matches()
-            .iter()
-            .fold(0, |acc, _| if acc == 0 { 1 } else { acc * 2 })
+        match self.match_count() {
+            0 => 0,
+            n => 1 << (n - 1),
+        }
     }
 }
 
-fn count_cards(s: &str) -> u32 {
-    let mut copies: HashMap<usize, u32> = HashMap::new();
+// how many copies of a card there were and where they came from
+#[derive(Debug, Clone, PartialEq)]
+struct CardCopies {
+    card: usize,
+    copies: u32,
+    // (card, copies) for every card that won copies of this one
+    contributions: Vec<(usize, u32)>,
+}
 
-    for (i, line) in s.lines().enumerate() {
-        let card = parse_card(line);
+impl Display for CardCopies {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Card {}: {} copies, original", self.card, self.copies)?;
+        for (card, copies) in self.contributions.iter() {
+            write!(f, ", {} from card {}", copies, card)?;
+        }
+        Ok(())
+    }
+}
+
+// Cards come in one at a time, a card only ever wins copies of the cards right after it,
+// so the copies still to come fit in a ring buffer as long as the most matches a card has
+struct Cascade {
+    pending: [u32; PENDING],
+    // contributions to the pending copies, only kept when tracing
+    pending_from: Option<Vec<Vec<(usize, u32)>>>,
+    card: usize,
+    total: u32,
+    trace: Vec<CardCopies>,
+}
+
+impl Cascade {
+    fn new() -> Cascade {
+        Cascade {
+            pending: [0; PENDING],
+            pending_from: None,
+            card: 0,
+            total: 0,
+            trace: Vec::new(),
+        }
+    }
 
-        let next_copies_count = *copies.entry(i).and_modify(|v| *v += 1).or_insert(1);
-        
-        for next_card in i+1..i + card.get_matches().len() + 1 {
-            copies.entry(next_card).
-                and_modify(|v| *v = *v + next_copies_count).
-                or_insert(next_copies_count);
+    fn with_trace() -> Cascade {
+        Cascade {
+            pending_from: Some(vec![Vec::new(); PENDING]),
+            ..Cascade::new()
         }
     }
 
-    copies.values().sum()
+    // returns how many copies of the card there are, the original included
+    fn push(&mut self, card: &Card) -> u32 {
+        let slot = self.card % PENDING;
+        let copies = std::mem::take(&mut self.pending[slot]) + 1;
+        self.card += 1;
+        self.total += copies;
+
+        let matches = card.match_count();
+        for i in 1..=matches {
+            self.pending[(slot + i) % PENDING] += copies;
+        }
+
+        if let Some(pending_from) = self.pending_from.as_mut() {
+            self.trace.push(CardCopies {
+                card: self.card,
+                copies,
+                contributions: std::mem::take(&mut pending_from[slot]),
+            });
+            for i in 1..=matches {
+                pending_from[(slot + i) % PENDING].push((self.card, copies));
+            }
+        }
+
+        copies
+    }
+
+    fn total(&self) -> u32 {
+        self.total
+    }
+
+    fn trace(&self) -> Option<&[CardCopies]> {
+        self.pending_from.as_ref().map(|_| self.trace.as_slice())
+    }
+}
+
+#[cfg(test)]
+fn count_cards(s: &str) -> u32 {
+    let mut cascade = Cascade::new();
+    for line in s.lines() {
+        cascade.push(&parse_card(line));
+    }
+    cascade.total()
 }
 
 fn parse_card(s: &str) -> Card {
-    let mut parts = s.split(":").nth(1).unwrap().split("|");
+    let mut parts = s.split(':').nth(1).unwrap().split('|');
 
     Card {
         winning_numbers: parts
@@ -83,6 +191,13 @@ fn parse_card(s: &str) -> Card {
 mod tests {
     use super::*;
 
+    const CARDS: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+        Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+        Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+        Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+        Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
     #[test]
     fn test_parse_card() {
         let card1 = parse_card("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
@@ -91,6 +206,7 @@ mod tests {
             format!("{:?}", card1),
         );
 
+        assert_eq!(vec![48, 83, 86, 17], card1.get_matches());
         assert_eq!(8, card1.get_points());
 
         let card2 = parse_card("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19");
@@ -102,14 +218,62 @@ mod tests {
 
     #[test]
     fn test_count_cards() {
-        let count = count_cards(
-            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-        Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-        Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-        Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-        Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-        Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        assert_eq!(30, count_cards(CARDS));
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut cascade = Cascade::with_trace();
+        let copies: Vec<u32> = CARDS
+            .lines()
+            .map(|line| cascade.push(&parse_card(line)))
+            .collect();
+        assert_eq!(vec![1, 2, 4, 8, 14, 1], copies);
+
+        let trace = cascade.trace().unwrap();
+        assert_eq!(6, trace.len());
+        assert_eq!(
+            CardCopies {
+                card: 4,
+                copies: 8,
+                contributions: vec![(1, 1), (2, 2), (3, 4)],
+            },
+            trace[3]
+        );
+        assert_eq!(
+            "Card 5: 14 copies, original, 1 from card 1, 4 from card 3, 8 from card 4",
+            trace[4].to_string()
         );
-        assert_eq!(30, count);
+        assert_eq!(Vec::<(usize, u32)>::new(), trace[5].contributions);
+
+        assert_eq!(None, Cascade::new().trace());
+    }
+
+    #[test]
+    fn test_ring_wraps_around() {
+        // wins a copy of each of the next 128 cards, as many as the ring has slots
+        let all: Vec<String> = (0..128).map(|n| n.to_string()).collect();
+        let line = format!("Card 1: {} | {}", all.join(" "), all.join(" "));
+        let big = parse_card(&line);
+        assert_eq!(128, big.match_count());
+
+        let one = parse_card("Card 2: 1 2 | 1 3");
+        let none = parse_card("Card 3: 1 2 | 3 4");
+
+        let mut cascade = Cascade::with_trace();
+        assert_eq!(1, cascade.push(&one));
+        // one copy from the card before, none left over from going around the ring
+        assert_eq!(2, cascade.push(&big));
+        let copies: Vec<u32> = (0..300).map(|_| cascade.push(&none)).collect();
+
+        assert_eq!(vec![3; 128], copies[..128]);
+        assert_eq!(vec![1; 172], copies[128..]);
+        assert_eq!(1 + 2 + 3 * 128 + 172, cascade.total());
+
+        let trace = cascade.trace().unwrap();
+        assert_eq!(vec![(1, 1)], trace[1].contributions);
+        assert_eq!(vec![(2, 2)], trace[2].contributions);
+        assert_eq!(vec![(2, 2)], trace[129].contributions);
+        assert!(trace[130].contributions.is_empty());
     }
 }
@@ -1,26 +1,37 @@
-use std::{env, fs, cmp, collections::HashMap};
+use std::fs;
 
 fn main() {
-    let input = "Time:        63     78     94     68
-    Distance:   411   1274   2047   1035";
-    let ways_to_win_1 = ways_to_win_in_races(parse_races(input));
-    println!("Part 1: {}", ways_to_win_1);
-
-    let input2 = "Time:        63789468
-    Distance:   411127420471035";
-    let ways_to_win_2 = ways_to_win_in_races(parse_races(input2));
-    println!("Part 2: {}", ways_to_win_2);
+    let input = fs::read_to_string("./src/input.txt").expect("could not read file");
+    println!("Part 1: {}", ways_to_win_in_races(parse_races(&input, Part::One)));
+    println!("Part 2: {}", ways_to_win_in_races(parse_races(&input, Part::Two)));
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Race(i64, i64);
+struct Race(u64, u64);
+
+// part one reads every number as a separate race,
+// part two ignores the spaces and reads each line as a single number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    One,
+    Two,
+}
+
+fn parse_races(s: &str, part: Part) -> Vec<Race> {
+    let mut lines = s.trim().lines();
+    let times = parse_numbers(lines.next().expect("times are missing"), part);
+    let distances = parse_numbers(lines.next().expect("distances are missing"), part);
 
-fn parse_races(s: &str) -> Vec<Race> {
-    let mut lines = s.lines();
-    let times: Vec<i64> = lines.next().unwrap().split(":").nth(1).unwrap().split_whitespace().map(|t| t.trim().parse().unwrap()).collect();
-    let distances: Vec<i64> = lines.next().unwrap().split(":").nth(1).unwrap().split_whitespace().map(|t| t.trim().parse().unwrap()).collect();
+    times.into_iter().zip(distances).map(|pair| Race(pair.0, pair.1) ).collect()
+}
 
-    times.into_iter().zip(distances.into_iter()).map(|pair| Race(pair.0, pair.1) ).collect()
+fn parse_numbers(line: &str, part: Part) -> Vec<u64> {
+    let values = line.split(":").nth(1).unwrap();
+
+    match part {
+        Part::One => values.split_whitespace().map(|t| t.trim().parse().unwrap()).collect(),
+        Part::Two => vec![values.split_whitespace().collect::<String>().parse().unwrap()],
+    }
 }
 
 
@@ -32,54 +43,45 @@ fn parse_races(s: &str) -> Vec<Race> {
 // tth = speed = 3, time left = 7-3 = 4, distance = 4*3 = 12
 // tth = speed = 4, time left = 7-4 = 3, distance = 3*4 = 12
 // tth = speed = 5, time left = 7-5 = 2, distance = 2*5 = 10
-fn get_hold_duration_range_above_record(time: i64, record_distance: i64) -> (f64, f64) {
-    // let x = time to hold = speed = 1, y = distance = (time - speed) * speed
-
-    // ax^2+bx+c=y
-    // if x=0 then y=0 => c = 0, ax^2+bx = y
-    // if x=1 then y=(time-x)*x =              time-1  = a+b     => a = time-1-b
-    // if x=2 then y=(time-x)*x = (time-2)*2 = 2time-4 = 4a+2b
-
-    // 2time-4 = 4a+2b
-    // 2time-4 = 4time-4-4b + 2b
-    // time-2 = 2time-2-2b  + b = 2time-2-b
-    // time-2time = -2-b+2
-    // -time = -b
-    // time = b
-    // a = time-1-b = -1
-    // -x^2+time*x = 0
-
-    let b = time as f64;
-    let a = -1f64;
-
-    let d = b*b-(4f64*a*(-record_distance as f64));
-    let sqrt_d = (d as f64).sqrt();
-    let x1 = (-b+sqrt_d)/-2f64;
-    let x2 = (-b-sqrt_d)/-2f64;
-
-    println!(
-        "time={}, record_distance={}, b={}, d={}, sqrtd={}, x1={}, x2={}", 
-        time, record_distance, b, d, sqrt_d, x1, x2
-    );
-
-    (x1, x2)
+//
+// distance = (time - x) * x, we need (time - x) * x > record,
+// i.e. x^2 - time*x + record < 0, strictly between the roots (time -+ sqrt(d)) / 2
+// where d = time^2 - 4*record. Returns the first and the last winning hold duration.
+fn get_hold_duration_range_above_record(time: u64, record_distance: u64) -> Option<(u64, u64)> {
+    let time = time as u128;
+    let record_distance = record_distance as u128;
+    let beats_record = |x: u128| x <= time && (time - x) * x > record_distance;
+
+    let d = (time * time).checked_sub(4 * record_distance)?;
+    let sqrt_d = d.isqrt();
+
+    // the integer root can be off by one from the real one, so step to the exact boundary
+    let mut first = (time - sqrt_d.min(time)) / 2;
+    while first > 0 && beats_record(first - 1) {
+        first -= 1;
+    }
+    while first <= time / 2 && !beats_record(first) {
+        first += 1;
+    }
+
+    if !beats_record(first) {
+        return None;
+    }
+
+    // the distances are symmetric around time / 2
+    Some((first as u64, (time - first) as u64))
 }
 
-fn ways_to_win(x1: f64, x2: f64) -> u64 {
-    let ways_float = (x2-x1);
-    let ways_int = if ways_float.fract() > 0f64 { ways_float.round().trunc() as u64 } else { ways_float.round().trunc() as u64 - 1 };
-    println!(
-        "ways to win between {} and {}: {} float or {} int, fract {}", 
-        x1, x2, ways_float, ways_int, ways_float.fract()
-    );
-    ways_int
-    
+fn ways_to_win(range: Option<(u64, u64)>) -> u64 {
+    match range {
+        Some((first, last)) => last - first + 1,
+        None => 0,
+    }
 }
 
 fn ways_to_win_in_races(races: Vec<Race>) -> u64 {
     races.iter().map(|r| {
-        let duration_range = get_hold_duration_range_above_record(r.0, r.1);
-        ways_to_win(duration_range.0, duration_range.1)
+        ways_to_win(get_hold_duration_range_above_record(r.0, r.1))
     }).product()
 }
 
@@ -91,24 +93,74 @@ mod tests {
     #[test]
     fn test_parse_races() {
         let races = parse_races("Time:      7  15   30
-        Distance:  9  40  200");
+        Distance:  9  40  200", Part::One);
         assert_eq!("[Race(7, 9), Race(15, 40), Race(30, 200)]", format!("{:?}", races));
 
         assert_eq!(4*8*9, ways_to_win_in_races(races));
     }
 
+    #[test]
+    fn test_parse_races_kerned() {
+        let races = parse_races("
+        Time:      7  15   30
+        Distance:  9  40  200
+        ", Part::Two);
+        assert_eq!("[Race(71530, 940200)]", format!("{:?}", races));
+
+        assert_eq!(71503, ways_to_win_in_races(races));
+    }
+
     #[test]
     fn test_get_hold_duration_range_above_record() {
-        let (mut x1, mut x2) = get_hold_duration_range_above_record(7, 9);
-        // assert_eq!((1.6972244, 5.3027754), (x1, x2));
-        assert_eq!(4, ways_to_win(x1, x2));
+        let mut range = get_hold_duration_range_above_record(7, 9);
+        assert_eq!(Some((2, 5)), range);
+        assert_eq!(4, ways_to_win(range));
+
+        range = get_hold_duration_range_above_record(15, 40);
+        assert_eq!(Some((4, 11)), range);
+        assert_eq!(8, ways_to_win(range));
 
-        (x1, x2) = get_hold_duration_range_above_record(15, 40);
-        // assert_eq!((-0.5311289, 7.531129), (x1, x2));
-        assert_eq!(8, ways_to_win(x1, x2));
+        range = get_hold_duration_range_above_record(30, 200);
+        assert_eq!(Some((11, 19)), range);
+        assert_eq!(9, ways_to_win(range));
 
-        (x1, x2) = get_hold_duration_range_above_record(30, 200);
-        // assert_eq!((-1.5, 8.5), (x1, x2));
-        assert_eq!(9, ways_to_win(x1, x2));
+        range = get_hold_duration_range_above_record(71530, 940200);
+        assert_eq!(71503, ways_to_win(range));
+
+        range = get_hold_duration_range_above_record(63789468, 411127420471035);
+        assert_eq!(Some((7274689, 56514779)), range);
+        assert_eq!(49240091, ways_to_win(range));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_perfect_square_boundaries() {
+        // d = 16, holding 3 or 7 only ties the record
+        assert_eq!(Some((4, 6)), get_hold_duration_range_above_record(10, 21));
+        // d = 0, the best hold only ties the record
+        assert_eq!(None, get_hold_duration_range_above_record(4, 4));
+        // d = 4, a single winning hold
+        assert_eq!(Some((2, 2)), get_hold_duration_range_above_record(4, 3));
+        // d < 0, the record can't be reached
+        assert_eq!(None, get_hold_duration_range_above_record(4, 5));
+        assert_eq!(Some((1, 4)), get_hold_duration_range_above_record(5, 0));
+        assert_eq!(None, get_hold_duration_range_above_record(0, 0));
+
+        // compare with trying every hold duration
+        for time in 0..60u64 {
+            for record in 0..(time * time / 4 + 2) {
+                let expected = (0..=time).filter(|x| (time - x) * x > record).count() as u64;
+                assert_eq!(
+                    expected,
+                    ways_to_win(get_hold_duration_range_above_record(time, record)),
+                    "{} {}",
+                    time,
+                    record
+                );
+            }
+        }
+
+        // large perfect square: roots 10^9 and 3 * 10^9
+        let range = get_hold_duration_range_above_record(4_000_000_000, 3_000_000_000_000_000_000);
+        assert_eq!(Some((1_000_000_001, 2_999_999_999)), range);
+    }
+}
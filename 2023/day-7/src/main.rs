@@ -1,4 +1,10 @@
-use std::{cmp::Ordering, collections::HashMap, env, fs, str::FromStr};
+use std::{
+    cmp::Ordering,
+    env,
+    fmt::{self, Display},
+    fs,
+    str::FromStr,
+};
 
 use itertools::Itertools;
 
@@ -9,20 +15,97 @@ fn main() {
         .expect("path is required: cargo run -- src/input.txt");
     let input = fs::read_to_string(path).expect("could not read file");
 
-    let games = game_from_str(&input);
-    println!("total winnings: {}", games.get_ranks().iter().sum::<u64>());
+    let games = game_from_str(&input).unwrap_or_else(|e| panic!("{}", e));
+    for (name, rules) in [("standard", Rules::standard()), ("jokers", Rules::jokers())] {
+        println!(
+            "total winnings, {}: {}",
+            name,
+            games.get_ranks(&rules).iter().sum::<u64>()
+        );
+    }
+
+    // cargo run -- src/input.txt --explain | --verify
+    if args.iter().any(|a| a == "--explain") {
+        let rules = Rules::jokers();
+        let sorted: Vec<&Hand> = games.hands.iter().sorted_by(|a, b| a.cmp_with(b, &rules)).collect();
+        for pair in sorted.windows(2) {
+            println!("{}", pair[1].explain(pair[0], &rules));
+        }
+    }
+
+    if args.iter().any(|a| a == "--verify") {
+        match verify_wildcards(&Rules::jokers(), 5) {
+            Ok(count) => println!("wildcard table matches brute force for {} hands", count),
+            Err(e) => println!("{}", e),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 enum Card {
-    J,
     Value(u8),
     T,
+    J,
     Q,
     K,
     A,
 }
 
+// how hands are ranked: the order of the cards from the weakest,
+// the card that can stand in for any other one, and if that card
+// is the weakest one when comparing hands of the same type
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules {
+    order: Vec<Card>,
+    wild: Option<Card>,
+    wild_lowest: bool,
+}
+
+impl Rules {
+    fn new(order: &str, wild: Option<Card>, wild_lowest: bool) -> Rules {
+        Rules {
+            order: cards_from_str(order),
+            wild,
+            wild_lowest,
+        }
+    }
+
+    fn standard() -> Rules {
+        Rules::new("23456789TJQKA", None, false)
+    }
+
+    fn jokers() -> Rules {
+        Rules::new("23456789TJQKA", Some(Card::J), true)
+    }
+
+    fn without_wild(&self) -> Rules {
+        Rules {
+            wild: None,
+            ..self.clone()
+        }
+    }
+
+    fn is_wild(&self, card: &Card) -> bool {
+        self.wild.as_ref() == Some(card)
+    }
+
+    fn card_rank(&self, card: &Card) -> usize {
+        if self.wild_lowest && self.is_wild(card) {
+            return 0;
+        }
+
+        self.order
+            .iter()
+            .position(|c| c == card)
+            .map(|i| i + 1)
+            .unwrap_or_else(|| panic!("card {:?} is not in the order", card))
+    }
+
+    fn cmp_cards(&self, a: &Card, b: &Card) -> Ordering {
+        self.card_rank(a).cmp(&self.card_rank(b))
+    }
+}
+
 impl FromStr for Card {
     type Err = String;
 
@@ -55,124 +138,237 @@ enum HandType {
     FiveOfAKind,
 }
 
+// works for hands of any length, e.g. 7 cards with counts [3, 3, 1] are a full house
+fn hand_type_from_counts(counts: &[usize]) -> HandType {
+    let first = counts.first().copied().unwrap_or(0);
+    let second = counts.get(1).copied().unwrap_or(0);
+
+    match (first, second) {
+        (5.., _) => HandType::FiveOfAKind,
+        (4, _) => HandType::FourOfAKind,
+        (3, 2..) => HandType::FullHouse,
+        (3, _) => HandType::ThreeOfAKind,
+        (2, 2) => HandType::TwoPair,
+        (2, _) => HandType::OnePair,
+        _ => HandType::HighCard,
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct Hand {
     cards: Vec<Card>,
     bid: u32,
 }
 
-#[derive(Debug, Clone)]
-struct HandStats {
-    jokers: usize,
-    same: usize,
-    pairs: usize,
-}
-
 impl Hand {
-    fn get_stats(&self) -> HandStats {
-        let counts = self.cards.iter().counts();
-        let counts_without_jokers = counts
-            .clone()
-            .into_iter()
-            .filter(|(card, _count)| !matches!(card, Card::J))
-            .collect::<HashMap<&Card, usize>>();
-
-        HandStats {
-            jokers: *counts.get(&Card::J).unwrap_or(&0),
-            same: counts_without_jokers
-                .clone()
-                .into_iter()
-                .max_by(|a, b| a.1.cmp(&b.1))
-                .map(|(_card, count)| count)
-                .unwrap_or(0),
-            pairs: counts_without_jokers
-                .clone()
-                .into_values()
-                .filter(|c| *c == 2)
-                .count(),
+    // how many times each card is in the hand, from the most common one,
+    // wild cards join the most common card
+    fn get_counts(&self, rules: &Rules) -> Vec<usize> {
+        let mut counts: Vec<usize> = self
+            .cards
+            .iter()
+            .filter(|c| !rules.is_wild(c))
+            .counts()
+            .into_values()
+            .sorted()
+            .rev()
+            .collect();
+
+        let wilds = self.cards.iter().filter(|c| rules.is_wild(c)).count();
+        match counts.first_mut() {
+            Some(most_common) => *most_common += wilds,
+            None if wilds > 0 => counts.push(wilds),
+            None => {}
         }
+
+        counts
     }
-    fn get_type(&self)  -> HandType {
-        let stats = self.get_stats();
-        let t = self.get_type_no_jokers(&stats);
 
-        if stats.jokers == 0 {
-            return t
-        }
+    fn get_type(&self, rules: &Rules) -> HandType {
+        hand_type_from_counts(&self.get_counts(rules))
+    }
 
-        match t {
-            HandType::HighCard => match stats.jokers {
-                1 => HandType::OnePair,
-                2 => HandType::ThreeOfAKind,
-                3 => HandType::FourOfAKind,
-                _ => HandType::FiveOfAKind,
-            }
-            HandType::OnePair => match stats.jokers {
-                1 => HandType::ThreeOfAKind,
-                2 => HandType::FourOfAKind,
-                _ => HandType::FiveOfAKind,
-            },
-            HandType::TwoPair => HandType::FullHouse,
-            HandType::ThreeOfAKind => if stats.jokers == 1 { HandType::FourOfAKind } else {HandType::FiveOfAKind},
-            HandType::FourOfAKind => HandType::FiveOfAKind,
-            _ => HandType::FiveOfAKind,
-        }
+    // the hand with every wild card replaced by the card giving the best type,
+    // all wild cards are best used as the same card
+    fn best_substitution(&self, rules: &Rules) -> Vec<Card> {
+        let Some(wild) = rules.wild else {
+            return self.cards.clone();
+        };
+
+        let plain = rules.without_wild();
+        rules
+            .order
+            .iter()
+            .filter(|c| **c != wild)
+            .map(|card| {
+                let cards: Vec<Card> = self
+                    .cards
+                    .iter()
+                    .map(|c| if *c == wild { *card } else { *c })
+                    .collect();
+                let hand_type = Hand { cards: cards.clone(), bid: self.bid }.get_type(&plain);
+                (hand_type, plain.card_rank(card), cards)
+            })
+            .max_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)))
+            .map(|(_, _, cards)| cards)
+            .unwrap_or_else(|| self.cards.clone())
     }
-    fn get_type_no_jokers(&self, stats: &HandStats) -> HandType {
-        if stats.same == 5 {
-            return HandType::FiveOfAKind;
-        } else if stats.same == 4 {
-            return HandType::FourOfAKind;
-        } else if stats.same == 3 && stats.pairs == 1 {
-            return HandType::FullHouse;
-        } else if stats.same == 3 {
-            return HandType::ThreeOfAKind;
-        } else if stats.pairs == 2 {
-            return HandType::TwoPair;
-        } else if stats.pairs == 1 {
-            return HandType::OnePair;
+
+    fn explain(&self, other: &Hand, rules: &Rules) -> Explanation {
+        let hand_type = self.get_type(rules);
+        let other_type = other.get_type(rules);
+
+        let tie_break = if hand_type == other_type {
+            self.cards
+                .iter()
+                .zip(other.cards.iter())
+                .enumerate()
+                .find(|(_, (a, b))| rules.cmp_cards(a, b) != Ordering::Equal)
+                .map(|(position, (card, other_card))| TieBreak {
+                    position,
+                    card: *card,
+                    other_card: *other_card,
+                })
         } else {
-            return HandType::HighCard;
+            None
+        };
+
+        Explanation {
+            cards: self.cards.clone(),
+            hand_type,
+            best_hand: self.best_substitution(rules),
+            other_cards: other.cards.clone(),
+            other_type,
+            tie_break,
+            ordering: self.cmp_with(other, rules),
         }
     }
-}
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        let type_a = self.get_type();
-        let type_b = other.get_type();
+    fn cmp_with(&self, other: &Self, rules: &Rules) -> Ordering {
+        let type_a = self.get_type(rules);
+        let type_b = other.get_type(rules);
 
         if type_a != type_b {
             return type_a.cmp(&type_b);
         }
 
         for (i, c) in self.cards.iter().enumerate() {
-            let cmp = c.cmp(&other.cards[i]);
+            let cmp = rules.cmp_cards(c, &other.cards[i]);
             if cmp != Ordering::Equal {
                 return cmp;
             }
         }
 
-        return Ordering::Equal;
+        Ordering::Equal
     }
 }
 
+// the first card that differs between two hands of the same type
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TieBreak {
+    position: usize,
+    card: Card,
+    other_card: Card,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Explanation {
+    cards: Vec<Card>,
+    hand_type: HandType,
+    best_hand: Vec<Card>,
+    other_cards: Vec<Card>,
+    other_type: HandType,
+    tie_break: Option<TieBreak>,
+    ordering: Ordering,
+}
+
+impl Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Card::Value(v) => write!(f, "{}", v),
+            Card::T => write!(f, "T"),
+            Card::J => write!(f, "J"),
+            Card::Q => write!(f, "Q"),
+            Card::K => write!(f, "K"),
+            Card::A => write!(f, "A"),
+        }
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cards = |cards: &[Card]| cards.iter().map(|c| c.to_string()).collect::<String>();
+
+        write!(f, "{} is {:?}", cards(&self.cards), self.hand_type)?;
+        if self.best_hand != self.cards {
+            write!(f, " as {}", cards(&self.best_hand))?;
+        }
+
+        write!(
+            f,
+            ", {:?} than {} ({:?})",
+            self.ordering,
+            cards(&self.other_cards),
+            self.other_type
+        )?;
+
+        if let Some(t) = &self.tie_break {
+            write!(f, ", card {} is {} against {}", t.position + 1, t.card, t.other_card)?;
+        }
+
+        Ok(())
+    }
+}
+
+// checks how get_type counts wild cards against trying every substitution of
+// the wild cards in every hand of the given length. Positions of the wild cards
+// don't change the type, so every multiset of substituted cards is tried once.
+fn verify_wildcards(rules: &Rules, len: usize) -> Result<usize, String> {
+    let plain = rules.without_wild();
+    let substitutes: Vec<Card> = rules.order.iter().filter(|c| !rules.is_wild(c)).copied().collect();
+    let mut checked = 0;
+
+    for cards in itertools::repeat_n(rules.order.iter().copied(), len).multi_cartesian_product() {
+        let hand = Hand { cards, bid: 0 };
+        let expected = hand.get_type(rules);
+
+        let fixed: Vec<Card> = hand.cards.iter().filter(|c| !rules.is_wild(c)).copied().collect();
+        let wilds = hand.cards.len() - fixed.len();
+
+        let brute_force = substitutes
+            .iter()
+            .copied()
+            .combinations_with_replacement(wilds)
+            .map(|extra| {
+                let cards = fixed.iter().copied().chain(extra).collect();
+                Hand { cards, bid: 0 }.get_type(&plain)
+            })
+            .max()
+            .unwrap_or_else(|| hand.get_type(&plain));
+
+        if brute_force != expected {
+            return Err(format!(
+                "{:?}: table gives {:?}, best substitution gives {:?}",
+                hand.cards, expected, brute_force
+            ));
+        }
+
+        checked += 1;
+    }
+
+    Ok(checked)
+}
+
 #[derive(Debug)]
 struct Game {
     hands: Vec<Hand>,
 }
 
 impl Game {
-    fn get_ranks(&self) -> Vec<u64> {
+    fn get_ranks(&self, rules: &Rules) -> Vec<u64> {
         self.hands
             .iter()
-            .sorted()
+            .sorted_by(|a, b| a.cmp_with(b, rules))
             .enumerate()
             .map(|(i, h)| {
                 (h.bid as u64) * (i + 1) as u64
@@ -181,21 +377,40 @@ impl Game {
     }
 }
 
-fn game_from_str(s: &str) -> Game {
-    Game {
-        hands: s.lines().map(|line| hand_from_str(line.trim())).collect(),
+// all hands in a game must have the same number of cards
+fn game_from_str(s: &str) -> Result<Game, String> {
+    let hands: Vec<Hand> = s
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(hand_from_str)
+        .collect();
+
+    if let Some(first) = hands.first() {
+        let len = first.cards.len();
+        if let Some((i, hand)) = hands.iter().enumerate().find(|(_, h)| h.cards.len() != len) {
+            return Err(format!(
+                "hand {} has {} cards, but hand 1 has {}",
+                i + 1,
+                hand.cards.len(),
+                len
+            ));
+        }
     }
+
+    Ok(Game { hands })
 }
 
-fn hand_from_str(s: &str) -> Hand {
-    let mut parts = s.split_whitespace();
-    let cards: Vec<Card> = parts
-        .next()
-        .unwrap()
-        .split("")
+fn cards_from_str(s: &str) -> Vec<Card> {
+    s.split("")
         .filter(|c| !c.is_empty())
         .map(|c| c.parse().unwrap())
-        .collect();
+        .collect()
+}
+
+fn hand_from_str(s: &str) -> Hand {
+    let mut parts = s.split_whitespace();
+    let cards = cards_from_str(parts.next().unwrap());
 
     Hand {
         cards: cards.clone(),
@@ -209,44 +424,70 @@ mod tests {
 
     #[test]
     fn test_hand_from_str() {
-        assert_eq!(HandType::FourOfAKind, hand_from_str("QQQJA 483").get_type());
+        let jokers = Rules::jokers();
+        assert_eq!(HandType::FourOfAKind, hand_from_str("QQQJA 483").get_type(&jokers));
 
         assert_eq!("Hand { cards: [Value(3), Value(2), T, Value(3), K], bid: 765 }", format!("{:?}", hand_from_str("32T3K 765")));
         assert_eq!("Hand { cards: [Q, Q, Q, J, A], bid: 483 }", format!("{:?}", hand_from_str("QQQJA 483")));
 
-        assert_eq!(HandType::OnePair, hand_from_str("32T3K 765").get_type());
-        assert_eq!(HandType::TwoPair, hand_from_str("KK677 483").get_type());
+        assert_eq!(HandType::OnePair, hand_from_str("32T3K 765").get_type(&jokers));
+        assert_eq!(HandType::TwoPair, hand_from_str("KK677 483").get_type(&jokers));
 
-        assert_eq!(HandType::FourOfAKind, hand_from_str("QQQJA 483").get_type());
-        assert_eq!(HandType::FourOfAKind, hand_from_str("T55J5 483").get_type());
-        assert_eq!(HandType::FourOfAKind, hand_from_str("KTJJT 483").get_type());
+        assert_eq!(HandType::FourOfAKind, hand_from_str("QQQJA 483").get_type(&jokers));
+        assert_eq!(HandType::FourOfAKind, hand_from_str("T55J5 483").get_type(&jokers));
+        assert_eq!(HandType::FourOfAKind, hand_from_str("KTJJT 483").get_type(&jokers));
 
-        assert_eq!(HandType::HighCard, hand_from_str("2345A 483").get_type());
+        assert_eq!(HandType::HighCard, hand_from_str("2345A 483").get_type(&jokers));
 
-        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJJ 483").get_type());
-        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJAAA 483").get_type());
-        assert_eq!(HandType::FiveOfAKind, hand_from_str("JAAAA 483").get_type());
-        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJA 483").get_type());
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJJ 483").get_type(&jokers));
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJAAA 483").get_type(&jokers));
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("JAAAA 483").get_type(&jokers));
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJA 483").get_type(&jokers));
 
-        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJA 483").get_type());
-        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJA 483").get_type());
-        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJAAA 483").get_type());
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJA 483").get_type(&jokers));
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJJJA 483").get_type(&jokers));
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("JJAAA 483").get_type(&jokers));
 
         assert_eq!(
             HandType::ThreeOfAKind,
-            hand_from_str("JJAKT 483").get_type()
+            hand_from_str("JJAKT 483").get_type(&jokers)
         );
     }
 
     #[test]
     fn test_cmp_hands() {
+        let jokers = Rules::jokers();
+        assert_eq!(
+            Ordering::Less,
+            hand_from_str("T55J5 1").cmp_with(&hand_from_str("QQQJA 1"), &jokers)
+        );
+        assert_eq!(
+            Ordering::Less,
+            hand_from_str("QQQJA 1").cmp_with(&hand_from_str("KTJJT 1"), &jokers)
+        );
+
+        // without jokers J is between T and Q, and doesn't stand in for anything
+        let standard = Rules::standard();
+        assert_eq!(
+            Ordering::Greater,
+            hand_from_str("T55J5 1").cmp_with(&hand_from_str("KTJJT 1"), &standard)
+        );
+        assert_eq!(
+            Ordering::Greater,
+            hand_from_str("JKKK2 1").cmp_with(&hand_from_str("TKKK2 1"), &standard)
+        );
+        assert_eq!(
+            Ordering::Greater,
+            hand_from_str("JKKK2 1").cmp_with(&hand_from_str("TKKK2 1"), &jokers)
+        );
+        // same type, J is the weakest card in tie-breaks
         assert_eq!(
             Ordering::Less,
-            hand_from_str("T55J5 1").cmp(&hand_from_str("QQQJA 1"))
+            hand_from_str("J2345 1").cmp_with(&hand_from_str("22345 1"), &jokers)
         );
         assert_eq!(
             Ordering::Less,
-            hand_from_str("QQQJA 1").cmp(&hand_from_str("KTJJT 1"))
+            hand_from_str("J2345 1").cmp_with(&hand_from_str("22345 1"), &standard)
         );
 
         // assert_eq!(Ordering::Less, hand_from_str("32T3K 765").cmp(&hand_from_str("QQQJA 483")));
@@ -266,7 +507,8 @@ mod tests {
         KK677 28
         KTJJT 220
         QQQJA 483",
-        );
+        )
+        .unwrap();
         assert_eq!(5, game.hands.len());
 
         game.hands.iter().for_each(|h| println!("{:?}", h));
@@ -280,6 +522,111 @@ mod tests {
         //     "[765, 440, 84, 2736, 2415]",
         //     format!("{:?}", game.get_ranks())
         // );
-        assert_eq!(5905u64, game.get_ranks().iter().sum());
+        assert_eq!(6440u64, game.get_ranks(&Rules::standard()).iter().sum());
+        assert_eq!(5905u64, game.get_ranks(&Rules::jokers()).iter().sum());
+    }
+
+    #[test]
+    fn test_rules() {
+        // a variant where queens are wild but still rank between J and K
+        let rules = Rules::new("23456789TJQKA", Some(Card::Q), false);
+        assert_eq!(HandType::FourOfAKind, hand_from_str("QQQJA 1").get_type(&rules));
+        assert_eq!(HandType::ThreeOfAKind, hand_from_str("QQQJA 1").get_type(&Rules::standard()));
+        assert_eq!(
+            Ordering::Greater,
+            hand_from_str("Q2345 1").cmp_with(&hand_from_str("J2345 1"), &rules)
+        );
+
+        // a reversed card order
+        let reversed = Rules::new("AKQJT98765432", None, false);
+        assert_eq!(
+            Ordering::Greater,
+            hand_from_str("23456 1").cmp_with(&hand_from_str("A2345 1"), &reversed)
+        );
+        assert_eq!(1, reversed.card_rank(&Card::A));
+        assert_eq!(0, Rules::jokers().card_rank(&Card::J));
+    }
+
+    #[test]
+    fn test_explain() {
+        let jokers = Rules::jokers();
+
+        let e = hand_from_str("KTJJT 1").explain(&hand_from_str("QQQJA 1"), &jokers);
+        assert_eq!(HandType::FourOfAKind, e.hand_type);
+        assert_eq!(cards_from_str("KTTTT"), e.best_hand);
+        assert_eq!(HandType::FourOfAKind, e.other_type);
+        assert_eq!(
+            Some(TieBreak { position: 0, card: Card::K, other_card: Card::Q }),
+            e.tie_break
+        );
+        assert_eq!(Ordering::Greater, e.ordering);
+        assert_eq!(
+            "KTJJT is FourOfAKind as KTTTT, Greater than QQQJA (FourOfAKind), card 1 is K against Q",
+            e.to_string()
+        );
+
+        let e = hand_from_str("JJJJJ 1").explain(&hand_from_str("32T3K 1"), &jokers);
+        assert_eq!(cards_from_str("AAAAA"), e.best_hand);
+        assert_eq!(None, e.tie_break);
+        assert_eq!("JJJJJ is FiveOfAKind as AAAAA, Greater than 32T3K (OnePair)", e.to_string());
+
+        let e = hand_from_str("32T3K 1").explain(&hand_from_str("32T3K 2"), &Rules::standard());
+        assert_eq!(cards_from_str("32T3K"), e.best_hand);
+        assert_eq!(None, e.tie_break);
+        assert_eq!(Ordering::Equal, e.ordering);
+    }
+
+    #[test]
+    fn test_verify_wildcards_small() {
+        // every five card hand out of four cards, one of them wild
+        let rules = Rules::new("23JA", Some(Card::J), true);
+        assert_eq!(Ok(4usize.pow(5)), verify_wildcards(&rules, 5));
+        assert_eq!(Ok(4usize.pow(5)), verify_wildcards(&rules.without_wild(), 5));
+
+        assert_eq!(Ok(13usize.pow(3)), verify_wildcards(&Rules::jokers(), 3));
+        assert_eq!(Ok(4usize.pow(7)), verify_wildcards(&rules, 7));
+    }
+
+    // cargo test --release verify_wildcards_all -- --ignored
+    #[test]
+    #[ignore]
+    fn test_verify_wildcards_all() {
+        assert_eq!(Ok(13usize.pow(5)), verify_wildcards(&Rules::jokers(), 5));
+    }
+
+    #[test]
+    fn test_hand_of_any_length() {
+        let jokers = Rules::jokers();
+        assert_eq!(HandType::ThreeOfAKind, hand_from_str("QJQ 1").get_type(&jokers));
+        assert_eq!(HandType::OnePair, hand_from_str("Q2Q 1").get_type(&jokers));
+        assert_eq!(HandType::HighCard, hand_from_str("A 1").get_type(&jokers));
+        assert_eq!(HandType::FullHouse, hand_from_str("2233344 1").get_type(&jokers));
+        assert_eq!(HandType::FullHouse, hand_from_str("22J3345 1").get_type(&jokers));
+        assert_eq!(HandType::FiveOfAKind, hand_from_str("222222A 1").get_type(&jokers));
+        assert_eq!(HandType::TwoPair, hand_from_str("2233456 1").get_type(&jokers));
+
+        assert_eq!(vec![3, 2, 1, 1], hand_from_str("22J3345 1").get_counts(&jokers));
+        assert_eq!(vec![3], hand_from_str("JJJ 1").get_counts(&jokers));
+
+        let game = game_from_str(
+            "QJQ 3
+            234 1
+            2A2 2",
+        )
+        .unwrap();
+        assert_eq!(vec![1, 4, 9], game.get_ranks(&jokers));
+    }
+
+    #[test]
+    fn test_game_from_str_mixed_lengths() {
+        assert_eq!(
+            "hand 3 has 4 cards, but hand 1 has 5",
+            game_from_str(
+                "32T3K 765
+                T55J5 684
+                KK67 28"
+            )
+            .unwrap_err()
+        );
     }
 }
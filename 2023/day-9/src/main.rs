@@ -1,68 +1,287 @@
-use std::fs;
+use std::{
+    fmt::{self, Display},
+    fs,
+};
 
 use itertools::Itertools;
 
 fn main() {
     let input = fs::read_to_string("./src/input.txt").expect("could not read file");
 
-    let sum_of_next_values: i32 = input.lines().map(|l| next_value(parse_line(l))).sum();
-    let sum_of_prev_values: i32 = input.lines().map(|l| prev_value(parse_line(l))).sum();
+    let mut sum_of_next_values: i128 = 0;
+    let mut sum_of_prev_values: i128 = 0;
 
-    println!("Next: {}, prev: {}", sum_of_next_values, sum_of_prev_values);
-}
+    for (i, line) in input.lines().enumerate() {
+        let data = parse_line(line);
+        match next_value(&data).and_then(|next| Ok((next, prev_value(&data)?))) {
+            Ok((next, prev)) => {
+                sum_of_next_values += next;
+                sum_of_prev_values += prev;
+            }
+            Err(e) => println!("line {}: {}", i + 1, e),
+        }
+    }
 
-fn get_diffs(data: Vec<i32>) -> Vec<Vec<i32>> {
-    let mut diffs: Vec<Vec<i32>> = Vec::new();
-    diffs.push(data.clone());
+    println!("Next: {}, prev: {}", sum_of_next_values, sum_of_prev_values);
 
-    let mut current_vec = data;
-    let mut all_zeroes = false;
+    if let Some(Ok(p)) = input.lines().next().map(|l| analyse(&parse_line(l))) {
+        println!("First line: degree {}, {}", p.degree, p);
+        println!(
+            "Value at 1000: {:?}, at -1000: {:?}",
+            p.value_at(1000),
+            p.value_at(-1000)
+        );
+    }
+}
 
-    while !all_zeroes {
-        let mut next_diffs: Vec<i32> = Vec::new();
-        all_zeroes = true;
+#[derive(Debug, PartialEq)]
+enum SequenceError {
+    Empty,
+    // the differences never reached all zeroes, no polynomial of degree < len - 1 fits,
+    // a single value can't show anything, not even that the sequence is constant
+    NotPolynomial(usize),
+    Overflow,
+}
 
-        for (current, next) in current_vec.iter().tuple_windows() {
-            let diff = next-current;
-            next_diffs.push(diff);
-            
-            if diff != 0 {
-                all_zeroes = false;
+impl Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self {
+            SequenceError::Empty => write!(f, "sequence is empty"),
+            SequenceError::NotPolynomial(1) => {
+                write!(f, "a single value can't show the sequence is polynomial")
             }
+            SequenceError::NotPolynomial(len) => write!(
+                f,
+                "sequence of {} values is not polynomial of degree < {}",
+                len,
+                len - 1
+            ),
+            SequenceError::Overflow => write!(f, "values don't fit in i128"),
         }
+    }
+}
+
+fn get_diffs(data: &[i128]) -> Result<Vec<Vec<i128>>, SequenceError> {
+    let mut diffs: Vec<Vec<i128>> = Vec::new();
+    diffs.push(data.to_vec());
+
+    let mut current_vec = data.to_vec();
+
+    while current_vec.iter().any(|v| *v != 0) {
+        let next_diffs: Vec<i128> = current_vec
+            .iter()
+            .tuple_windows()
+            .map(|(current, next)| next.checked_sub(*current))
+            .collect::<Option<_>>()
+            .ok_or(SequenceError::Overflow)?;
 
         diffs.push(next_diffs.clone());
         current_vec = next_diffs;
     }
 
-    diffs
+    Ok(diffs)
+}
+
+// Exact fraction, always reduced and with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rational {
+    num: i128,
+    den: i128,
 }
 
-fn next_value(data: Vec<i32>) -> i32 {
-    let diffs = get_diffs(data);
-    let mut next_value = *diffs.last().unwrap().last().unwrap();
+impl Rational {
+    // None if the reduced fraction doesn't fit in i128
+    fn new(num: i128, den: i128) -> Option<Rational> {
+        let g = gcd(num.unsigned_abs(), den.unsigned_abs()).max(1);
+        let num_abs = i128::try_from(num.unsigned_abs() / g).ok()?;
+        let den_abs = i128::try_from(den.unsigned_abs() / g).ok()?;
+        Some(Rational {
+            num: if (num < 0) != (den < 0) {
+                -num_abs
+            } else {
+                num_abs
+            },
+            den: den_abs,
+        })
+    }
 
-    for level in diffs.iter().rev().skip(1) {
-        next_value = level.last().unwrap() + next_value;
+    fn add(self, other: Rational) -> Option<Rational> {
+        // over the least common denominator, so only what has to be multiplied is
+        let g = gcd(self.den.unsigned_abs(), other.den.unsigned_abs()) as i128;
+        let num = self
+            .num
+            .checked_mul(other.den / g)?
+            .checked_add(other.num.checked_mul(self.den / g)?)?;
+        Rational::new(num, self.den.checked_mul(other.den / g)?)
     }
 
-    next_value
+    fn mul(self, other: Rational) -> Option<Rational> {
+        // cancel crosswise first, both fractions are already reduced
+        let a = Rational::new(self.num, other.den)?;
+        let b = Rational::new(other.num, self.den)?;
+        Rational::new(a.num.checked_mul(b.num)?, a.den.checked_mul(b.den)?)
+    }
 }
 
-fn prev_value(data: Vec<i32>) -> i32 {
-    let diffs = get_diffs(data);
-    let mut prev_value = *diffs.last().unwrap().first().unwrap();
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
 
-    for level in diffs.iter().rev().skip(1) {
-        let first = level.first().unwrap();
-        // println!("prev: {}, current: {}, result: {}", prev_value, first, first - prev_value);
-        prev_value = first - prev_value;
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
+}
 
-    prev_value
+// The polynomial through the sequence, with index 0 being its first value.
+// Kept in Newton's form: f(x) = sum of differences[k] * C(x, k),
+// where differences[k] is the first value on the k-th level of the difference table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Polynomial {
+    degree: usize,
+    differences: Vec<i128>,
 }
 
-fn parse_line(s: &str) -> Vec<i32> {
+// The minimal degree polynomial, as long as the data proves it: the level after
+// the last non-zero one has to have at least one value left, all of them zeroes.
+// Even a constant needs two values, so a single one is never enough, zero or not.
+fn analyse(data: &[i128]) -> Result<Polynomial, SequenceError> {
+    match data.len() {
+        0 => return Err(SequenceError::Empty),
+        1 => return Err(SequenceError::NotPolynomial(1)),
+        _ => {}
+    }
+
+    let diffs = get_diffs(data)?;
+    if diffs.last().is_some_and(|level| level.is_empty()) {
+        return Err(SequenceError::NotPolynomial(data.len()));
+    }
+
+    let differences: Vec<i128> = diffs
+        .iter()
+        .filter_map(|level| level.first().copied())
+        .collect();
+
+    let degree = differences.iter().rposition(|d| *d != 0).unwrap_or(0);
+
+    Ok(Polynomial {
+        degree,
+        differences: differences[..=degree].to_vec(),
+    })
+}
+
+impl Polynomial {
+    // C(x, k) is an integer for every integer x, negative ones included,
+    // and so is every partial product on the way to it. None if it doesn't fit in i128.
+    fn value_at(&self, x: i128) -> Option<i128> {
+        let mut result: i128 = 0;
+        let mut binomial: i128 = 1;
+
+        for (k, d) in self.differences.iter().enumerate() {
+            if k > 0 {
+                binomial = binomial.checked_mul(x - (k as i128 - 1))? / k as i128;
+            }
+            result = result.checked_add(d.checked_mul(binomial)?)?;
+        }
+
+        Some(result)
+    }
+
+    // coefficients of x^0, x^1, ... x^degree, None if any of them or a step
+    // on the way to them doesn't fit in i128
+    fn coefficients(&self) -> Option<Vec<Rational>> {
+        let mut result = vec![Rational { num: 0, den: 1 }; self.degree + 1];
+        // x * (x - 1) * ... * (x - k + 1) and k!
+        let mut falling: Vec<i128> = vec![1];
+        let mut factorial: i128 = 1;
+
+        for (k, d) in self.differences.iter().enumerate() {
+            if k > 0 {
+                let shift = k as i128 - 1;
+                let mut next = vec![0i128; falling.len() + 1];
+                for (i, c) in falling.iter().enumerate() {
+                    next[i + 1] = next[i + 1].checked_add(*c)?;
+                    next[i] = next[i].checked_sub(c.checked_mul(shift)?)?;
+                }
+                falling = next;
+                factorial = factorial.checked_mul(k as i128)?;
+            }
+
+            let scale = Rational::new(*d, factorial)?;
+            for (i, c) in falling.iter().enumerate() {
+                result[i] = result[i].add(scale.mul(Rational { num: *c, den: 1 })?)?;
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(coefficients) = self.coefficients() else {
+            return write!(f, "{}", SequenceError::Overflow);
+        };
+        let mut terms: Vec<String> = Vec::new();
+
+        for (power, c) in coefficients.iter().enumerate().rev() {
+            if c.num == 0 {
+                continue;
+            }
+
+            let sign = if c.num < 0 { "-" } else { "+" };
+            let abs = Rational {
+                num: c.num.abs(),
+                den: c.den,
+            };
+            let term = match (power, abs) {
+                (0, _) => abs.to_string(),
+                (_, Rational { num: 1, den: 1 }) => String::new(),
+                _ => format!("{}*", abs),
+            } + &match power {
+                0 => String::new(),
+                1 => "x".to_string(),
+                _ => format!("x^{}", power),
+            };
+
+            if terms.is_empty() {
+                terms.push(if sign == "-" {
+                    format!("-{}", term)
+                } else {
+                    term
+                });
+            } else {
+                terms.push(format!("{} {}", sign, term));
+            }
+        }
+
+        if terms.is_empty() {
+            return write!(f, "0");
+        }
+
+        write!(f, "{}", terms.join(" "))
+    }
+}
+
+fn next_value(data: &[i128]) -> Result<i128, SequenceError> {
+    analyse(data)?
+        .value_at(data.len() as i128)
+        .ok_or(SequenceError::Overflow)
+}
+
+fn prev_value(data: &[i128]) -> Result<i128, SequenceError> {
+    analyse(data)?.value_at(-1).ok_or(SequenceError::Overflow)
+}
+
+fn parse_line(s: &str) -> Vec<i128> {
     s.split_whitespace().map(|c| c.parse().unwrap()).collect()
 }
 
@@ -73,18 +292,152 @@ mod tests {
     #[test]
     fn test_prev_value() {
         let mut values = parse_line("10 13 16 21 30 45");
-        assert_eq!(5, prev_value(values));
+        assert_eq!(Ok(5), prev_value(&values));
 
         values = parse_line("1 3 6 10 15 21");
-        assert_eq!(0, prev_value(values));
+        assert_eq!(Ok(0), prev_value(&values));
     }
 
     #[test]
     fn test_next_value() {
         let mut values = parse_line("0 3 6 9 12 15");
-        assert_eq!(18, next_value(values));
+        assert_eq!(Ok(18), next_value(&values));
 
         values = parse_line("10 13 16 21 30 45");
-        assert_eq!(68, next_value(values));
+        assert_eq!(Ok(68), next_value(&values));
+    }
+
+    #[test]
+    fn test_analyse() {
+        let p = analyse(&parse_line("1 3 6 10 15 21")).unwrap();
+        assert_eq!(2, p.degree);
+        assert_eq!(vec![1, 2, 1], p.differences);
+        // (x + 1)(x + 2) / 2
+        assert_eq!(
+            Some(vec![
+                Rational { num: 1, den: 1 },
+                Rational { num: 3, den: 2 },
+                Rational { num: 1, den: 2 }
+            ]),
+            p.coefficients()
+        );
+        assert_eq!("1/2*x^2 + 3/2*x + 1", p.to_string());
+
+        let p = analyse(&parse_line("10 13 16 21 30 45")).unwrap();
+        assert_eq!(3, p.degree);
+        assert_eq!("1/3*x^3 - x^2 + 11/3*x + 10", p.to_string());
+
+        let p = analyse(&parse_line("-4 -4 -4")).unwrap();
+        assert_eq!(0, p.degree);
+        assert_eq!("-4", p.to_string());
+
+        assert_eq!("0", analyse(&parse_line("0 0 0")).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_value_at() {
+        let p = analyse(&parse_line("1 3 6 10 15 21")).unwrap();
+        assert_eq!(Some(1), p.value_at(0));
+        assert_eq!(Some(0), p.value_at(-1));
+        assert_eq!(Some(0), p.value_at(-2));
+        assert_eq!(Some(1), p.value_at(-3));
+        assert_eq!(Some(500000500000), p.value_at(999999));
+
+        // x^5 - 3x
+        let data: Vec<i128> = (0..8).map(|x: i128| x.pow(5) - 3 * x).collect();
+        let p = analyse(&data).unwrap();
+        assert_eq!(5, p.degree);
+        for x in [-1000i128, -7, 0, 13, 1_000_000, 10_000_000] {
+            assert_eq!(Some(x.pow(5) - 3 * x), p.value_at(x), "{}", x);
+        }
+
+        // too far out for i128
+        assert_eq!(None, p.value_at(10i128.pow(9)));
+    }
+
+    #[test]
+    fn test_coefficients_overflow() {
+        // x^25 - x^24, every value and difference fits but the Newton terms are large
+        let data: Vec<i128> = (0..32).map(|x: i128| x.pow(25) - x.pow(24)).collect();
+        let p = analyse(&data).unwrap();
+        assert_eq!(25, p.degree);
+        assert!(p.value_at(32).is_some());
+        assert_eq!("x^25 - x^24", p.to_string());
+
+        // C(x, 34), the 34! under it doesn't fit in i128
+        let mut data = vec![0i128; 34];
+        data.extend([1, 35]);
+        let p = analyse(&data).unwrap();
+        assert_eq!(34, p.degree);
+        assert_eq!(None, p.coefficients());
+        assert_eq!("values don't fit in i128", p.to_string());
+    }
+
+    #[test]
+    fn test_rational() {
+        assert_eq!(Some(Rational { num: -1, den: 2 }), Rational::new(3, -6));
+        assert_eq!(Some(Rational { num: 0, den: 1 }), Rational::new(0, -5));
+        assert_eq!(
+            Some(Rational { num: 1, den: 1 }),
+            Rational::new(i128::MIN, i128::MIN)
+        );
+        assert_eq!(None, Rational::new(i128::MIN, 1));
+
+        // the denominators share a factor that would overflow their product
+        let a = Rational::new(1, 3 * 10i128.pow(30)).unwrap();
+        let b = Rational::new(1, 6 * 10i128.pow(30)).unwrap();
+        assert_eq!(Rational::new(1, 2 * 10i128.pow(30)), a.add(b));
+        assert_eq!(
+            Some(Rational { num: 1, den: 1 }),
+            a.mul(Rational {
+                num: 3 * 10i128.pow(30),
+                den: 1
+            })
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_not_polynomial() {
+        // powers of two repeat themselves on every level
+        let data = parse_line("1 2 4 8 16 32 64 128");
+        assert_eq!(Err(SequenceError::NotPolynomial(8)), analyse(&data));
+        assert_eq!(Err(SequenceError::NotPolynomial(8)), next_value(&data));
+        assert_eq!(
+            "sequence of 8 values is not polynomial of degree < 7",
+            SequenceError::NotPolynomial(8).to_string()
+        );
+
+        // alternating signs double on every level
+        let data = parse_line("1 -1 1 -1 1 -1");
+        assert_eq!(Err(SequenceError::NotPolynomial(6)), analyse(&data));
+
+        // a single value can't show that it's constant
+        assert_eq!(Err(SequenceError::NotPolynomial(1)), analyse(&[7]));
+        assert_eq!(Err(SequenceError::NotPolynomial(1)), analyse(&[0]));
+        assert_eq!(
+            "a single value can't show the sequence is polynomial",
+            SequenceError::NotPolynomial(1).to_string()
+        );
+        assert_eq!(Err(SequenceError::Empty), analyse(&[]));
+
+        assert_eq!(
+            Err(SequenceError::Overflow),
+            analyse(&[i128::MIN, i128::MAX])
+        );
+    }
+
+    #[test]
+    fn test_minimal_degree() {
+        // degree 6 needs all 8 values to show it
+        let data: Vec<i128> = (0..8).map(|x: i128| x.pow(6)).collect();
+        assert_eq!(6, analyse(&data).unwrap().degree);
+        assert_eq!(Err(SequenceError::NotPolynomial(7)), analyse(&data[..7]));
+
+        // a polynomial piece followed by a jump is not a polynomial
+        let data = parse_line("0 1 2 3 4 5 100");
+        assert_eq!(Err(SequenceError::NotPolynomial(7)), analyse(&data));
+
+        assert_eq!(0, analyse(&[7, 7]).unwrap().degree);
+        assert_eq!(0, analyse(&[0, 0]).unwrap().degree);
+    }
+}